@@ -178,6 +178,10 @@ pub enum Document<'a> {
 
     /// A string that is cheap to copy
     EcoString(EcoString),
+
+    /// Prints nothing, but marks a position in the output. The byte offset at
+    /// which each mark ends up is returned by `to_pretty_string_with_marks`.
+    Mark(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -321,6 +325,9 @@ fn fits(
             Document::String(s) => current_width += s.len() as isize,
             Document::EcoString(s) => current_width += s.len() as isize,
 
+            // Marks take up no space.
+            Document::Mark(_) => (),
+
             // If we get to a break we need to first see if it has to be
             // rendered as its unbroken or broken string, depending on the mode.
             Document::Break { unbroken, .. } => match mode {
@@ -380,8 +387,9 @@ pub enum BreakKind {
     Strict,
 }
 
-fn format(
-    writer: &mut impl Utf8Writer,
+fn format<W: Utf8Writer>(
+    writer: &mut W,
+    mark: &mut impl FnMut(&W, usize),
     limit: isize,
     mut width: isize,
     mut docs: im::Vector<(isize, Mode, &Document<'_>)>,
@@ -488,6 +496,9 @@ fn format(
                 writer.str_write(s)?;
             }
 
+            // Marks print nothing, the writer is just told where they are.
+            Document::Mark(id) => mark(writer, *id),
+
             // If multiple documents need to be printed, then they are all
             // pushed to the front of the queue and will be printed one by one.
             Document::Vec(vec) => {
@@ -637,10 +648,28 @@ impl<'a> Document<'a> {
 
     pub fn pretty_print(&self, limit: isize, writer: &mut impl Utf8Writer) -> Result<()> {
         let docs = im::vector![(0, Mode::Unbroken, self)];
-        format(writer, limit, 0, docs)?;
+        format(writer, &mut |_, _| (), limit, 0, docs)?;
         Ok(())
     }
 
+    /// Renders the document like `to_pretty_string`, also returning the id of
+    /// each `Mark` in it along with the byte offset in the output where it
+    /// ended up, in the order they were printed.
+    pub fn to_pretty_string_with_marks(self, limit: isize) -> (String, Vec<(usize, usize)>) {
+        let mut buffer = String::new();
+        let mut marks = vec![];
+        let docs = im::vector![(0, Mode::Unbroken, &self)];
+        format(
+            &mut buffer,
+            &mut |buffer: &String, id| marks.push((id, buffer.len())),
+            limit,
+            0,
+            docs,
+        )
+        .expect("Writing to string buffer failed");
+        (buffer, marks)
+    }
+
     /// Returns true when the document contains no printable characters
     /// (whitespace and newlines are considered printable characters).
     pub fn is_empty(&self) -> bool {
@@ -650,6 +679,7 @@ impl<'a> Document<'a> {
             EcoString(s) => s.is_empty(),
             String(s) => s.is_empty(),
             Str(s) => s.is_empty(),
+            Mark(_) => true,
             // assuming `broken` and `unbroken` are equivalent
             Break { broken, .. } => broken.is_empty(),
             ForceBroken(d) | Nest(_, _, _, d) | Group(d) | NextBreakFits(d, _) => d.is_empty(),
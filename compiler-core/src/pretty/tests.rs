@@ -308,6 +308,31 @@ fn empty_documents() {
     assert!("".to_doc().append("".to_doc()).is_empty());
     assert!(!"foo".to_doc().append("".to_doc()).is_empty());
     assert!(!"".to_doc().append("foo".to_doc()).is_empty());
+
+    // marks
+    assert!(Mark(0).is_empty());
+}
+
+#[test]
+fn marks_test() {
+    let doc = Vec(vec![
+        Mark(0),
+        "foo".to_doc(),
+        Mark(1),
+        break_(",", ", "),
+        Mark(2),
+        "bar".to_doc(),
+    ])
+    .group();
+
+    assert_eq!(
+        doc.clone().to_pretty_string_with_marks(80),
+        ("foo, bar".into(), vec![(0, 0), (1, 3), (2, 5)])
+    );
+    assert_eq!(
+        doc.to_pretty_string_with_marks(5),
+        ("foo,\nbar".into(), vec![(0, 0), (1, 3), (2, 5)])
+    );
 }
 
 #[test]
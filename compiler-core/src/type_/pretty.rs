@@ -1,20 +1,51 @@
-use super::{Type, TypeVar};
+use super::{
+    is_prelude_module,
+    printer::{NamedTypeNames, TypeNames},
+    PreludeType, Type, TypeVar,
+};
 use crate::{
     docvec,
+    error::{Error, FileIoAction, FileKind},
+    io::Utf8Writer,
+    parse::lexer::str_to_keyword,
     pretty::{nil, *},
 };
-use ecow::EcoString;
-use std::sync::Arc;
+use camino::Utf8PathBuf;
+use debug_ignore::DebugIgnore;
+use ecow::{eco_format, EcoString};
+use itertools::Itertools;
+use lsp_types::{SemanticToken, SemanticTokenType};
+use serde_json::json;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::Arc,
+};
+use strum::IntoEnumIterator;
 
 #[cfg(test)]
 use super::*;
-#[cfg(test)]
-use std::cell::RefCell;
 
 #[cfg(test)]
 use pretty_assertions::assert_eq;
 
 const INDENT: isize = 2;
+const DEFAULT_MAX_WIDTH: isize = 80;
+
+/// The legend that the `token_type` of semantic tokens produced by
+/// `Printer::semantic_tokens` indexes into.
+pub const TYPE_SEMANTIC_TOKEN_LEGEND: [SemanticTokenType; 6] = [
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::COMMENT,
+];
+
+/// A transformation applied to the output of `Printer::pretty_print`.
+pub type PostProcessor = Box<dyn Fn(String) -> String + Send + Sync>;
 
 #[derive(Debug, Default)]
 pub struct Printer {
@@ -22,6 +53,184 @@ pub struct Printer {
     uid: u64,
     // A mapping of printd type names to the module that they are defined in.
     printed_types: im::HashMap<EcoString, EcoString>,
+    // The modules of the named types that have been printed, or are about to
+    // be, used to tell if a qualifier can be shortened to the last segment of
+    // the module name.
+    printed_modules: im::HashSet<EcoString>,
+    // When set, the package of the first printed type with each module and
+    // type name, so a type with the same module and name from another package
+    // can be told apart from it.
+    printed_packages: Option<im::HashMap<(EcoString, EcoString), EcoString>>,
+    // The type names that had to be qualified because they clash with a type
+    // of the same name from another module.
+    ambiguous_names: im::HashSet<EcoString>,
+    // A mapping of module names to the qualifier to display for them, in place
+    // of the module name itself.
+    qualifier_overrides: im::HashMap<EcoString, EcoString>,
+    // When there are qualifier overrides, the type names that are used from
+    // more than one module, which are always qualified so that each is shown
+    // with its own qualifier.
+    clashing_names: im::HashSet<EcoString>,
+    // When set types are printed as they would be written in an annotation in
+    // the module these names are in scope in, and the modules that would need
+    // importing are recorded in `referenced_modules`.
+    type_names: Option<TypeNames>,
+    referenced_modules: im::HashSet<EcoString>,
+    // The qualifier for each module that would need importing into the module
+    // of `type_names`: the last segment of the module name or, if another
+    // module is already qualified with that, the whole module name with `_`
+    // in place of each `/`.
+    import_qualifiers: im::HashMap<EcoString, EcoString>,
+    // In strict mode a type imported unqualified into the module of
+    // `type_names` is qualified anyway if a type from that module with the
+    // same name is also printed.
+    strict: bool,
+    // Short names to print in place of named types, keyed by module and type
+    // name, and the ones that have been printed in the order they were used.
+    type_abbreviations: im::HashMap<(EcoString, EcoString), EcoString>,
+    used_abbreviations: Vec<(EcoString, EcoString)>,
+    // Type aliases without parameters to expand to the type they stand for,
+    // keyed by module and alias name. When a depth is given expansion stops
+    // after that many aliases have been followed, `alias_hops` being the
+    // number currently being followed. An alias already being followed is
+    // never expanded again, so aliases that refer to each other terminate.
+    type_aliases: im::HashMap<(EcoString, EcoString), Arc<Type>>,
+    // Names of type aliases to print in place of the types they stand for,
+    // keyed by `alias_table_key`.
+    alias_table: im::HashMap<(EcoString, EcoString), EcoString>,
+    alias_expansion_depth: Option<usize>,
+    alias_hops: usize,
+    expanding_aliases: HashSet<(EcoString, EcoString)>,
+    // Type variables that inference gave a default type, keyed by variable
+    // id, printed as that type followed by a `/* defaulted */` mark.
+    defaulted_vars: im::HashMap<u64, Arc<Type>>,
+    // Type variables that stand for holes in incomplete code, printed as `_`.
+    hole_ids: im::HashSet<u64>,
+    // While rendering with `render_marked`, what each `Document::Mark` placed
+    // in the document stands for, indexed by the mark's id.
+    marks: Option<Vec<Marked>>,
+    // The argument indices leading from the root of the type being printed to
+    // the child currently being printed.
+    node_path: Vec<usize>,
+    // Bit widths to annotate the prelude `Int` and `Float` types with, keyed by
+    // type name.
+    numeric_widths: im::HashMap<EcoString, u8>,
+    // Tuples with at most this many elements are never broken over multiple
+    // lines.
+    tuple_single_line_max_elems: Option<usize>,
+    // Whether the breaks of the document being printed are always rendered
+    // unbroken, for a tuple that has to stay on a single line.
+    single_line: bool,
+    // Whether argument lists broken over multiple lines are printed without a
+    // trailing comma after the last argument.
+    omit_trailing_commas: bool,
+    // When a single argument type wraps itself at least this many times in a
+    // row the repetition is collapsed, e.g. `List³(Int)`.
+    repeat_wrapper_min: Option<usize>,
+    // When set, the arguments of types nested more deeply than this are
+    // printed as `…`. `depth` is how deeply nested the type being printed is.
+    truncation_depth: Option<usize>,
+    depth: usize,
+    // Once this many type variables have been named any others are printed as
+    // `_`.
+    max_distinct_vars: Option<usize>,
+    var_naming: VarNaming,
+    unbound_style: UnboundStyle,
+    // Whether type variables are printed as their raw ids, e.g. `#2231`, rather
+    // than being named.
+    raw_var_ids: bool,
+    // Whether to surround type and module names that are not valid bare
+    // identifiers with backticks.
+    quote_unusual_names: bool,
+    // Names longer than this are shortened with an ellipsis in the middle.
+    max_name_length: Option<usize>,
+    // Whether every named type is qualified with its full module path, even
+    // prelude types and types that would otherwise be printed unqualified.
+    full_qualification: bool,
+    // Whether prelude types are always qualified as `gleam.Int` and so on.
+    explicit_prelude: bool,
+    // Whether prelude types are never qualified, even when their name clashes
+    // with another printed type.
+    bare_prelude: bool,
+    // When not empty each type variable is followed by the number of times it
+    // appears in the type being printed, keyed by variable id.
+    var_counts: HashMap<u64, usize>,
+    // Whether JSON descriptions of types include how each part is displayed.
+    json_display: bool,
+    // The addresses of the type variables currently being printed, so that a
+    // cycle of links is detected rather than recursing forever.
+    visiting_vars: HashSet<usize>,
+    // Whether `pretty_print` ends with a line naming the type variables it
+    // printed, which are collected in `legend_vars` as they are printed.
+    var_legend: bool,
+    legend_vars: Vec<EcoString>,
+    // Applied to the final string produced by `pretty_print` and
+    // `pretty_print_width`.
+    post_processor: DebugIgnore<Option<PostProcessor>>,
+}
+
+/// How names are chosen for type variables that have not been given one.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum VarNaming {
+    /// `a`, `b`, ..., `z`, `aa`, `ab`, ...
+    #[default]
+    Lowercase,
+    /// `A`, `B`, ..., `Z`, `AA`, `AB`, ...
+    Uppercase,
+    /// The prefix followed by a number counting up from zero, e.g. `t0`,
+    /// `t1`, `t2`.
+    Numbered { prefix: EcoString },
+}
+
+/// How unbound type variables are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnboundStyle {
+    /// As a letter, the same as generic type variables.
+    #[default]
+    Letter,
+    /// As `?`, to highlight that the type has not been fully inferred. This is
+    /// useful in diagnostics for public functions that are missing an
+    /// annotation.
+    Question,
+}
+
+/// A rendered type ready to be pasted into a module as an annotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyPayload {
+    /// The type as it would be written in the module.
+    pub annotation: String,
+    /// The import statements the module needs for the annotation to be valid.
+    pub imports: Vec<String>,
+}
+
+/// A position in a rendered type that a structural editor can target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub kind: PlaceholderKind,
+    /// The byte range of the position in the rendered type.
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    /// An argument, tuple element, or return type, which can be replaced by
+    /// another type.
+    Slot,
+    Module,
+    TypeName,
+    TypeVar,
+    /// A hole, printed as `_`.
+    Hole,
+}
+
+/// How a public type changed between two versions of a package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compat {
+    Unchanged,
+    /// Changed in a way that is still compatible with existing code.
+    Widened,
+    /// Changed in a way that existing code may no longer compile with.
+    Broken,
 }
 
 impl Printer {
@@ -33,169 +242,2694 @@ impl Printer {
         self.names = names;
     }
 
+    /// Print the type variable with the given id using this name, keeping
+    /// any names already given to other type variables. Names are not given
+    /// to other type variables once they have been taken.
+    ///
+    pub fn assign_name(&mut self, id: u64, name: EcoString) {
+        let _ = self.names.insert(id, name);
+    }
+
+    /// Forget the names given to type variables so far, so the next type
+    /// printed starts again from `a`, while keeping the decisions made about
+    /// which type names need qualifying.
+    ///
+    pub fn reset_var_counter(&mut self) {
+        self.names = im::HashMap::new();
+        self.uid = 0;
+    }
+
+    /// When printing a type as it would be written in a module, qualify types
+    /// imported unqualified if a type of the same name defined in that module is
+    /// also printed, rather than printing the ambiguous bare name for both.
+    ///
+    pub fn with_strict(&mut self) {
+        self.strict = true;
+    }
+
+    /// Print types as they would be written in an annotation in the module
+    /// the given names are in scope in: types from that module are not
+    /// qualified, types imported unqualified are printed with the name they
+    /// are imported as, and types from imported modules are qualified with the
+    /// name the module is imported as.
+    ///
+    /// Type variables in scope in the module, such as those declared by the
+    /// enclosing function, are printed with their names. Other type variables
+    /// are given names that do not clash with them.
+    ///
+    pub fn with_type_names(&mut self, names: TypeNames) {
+        self.type_names = Some(names);
+    }
+
+    /// Display the given qualifiers for types from these modules whenever they
+    /// need to be qualified, rather than using the module name. They are not
+    /// used when printing types as they would be written in a module, where
+    /// the module's imports decide the qualifiers.
+    ///
+    pub fn with_qualifier_overrides(&mut self, overrides: im::HashMap<EcoString, EcoString>) {
+        self.qualifier_overrides = overrides;
+    }
+
+    /// Print named types using the given short names, keyed by module and type
+    /// name. The ones that were used can be listed with `abbreviation_legend`.
+    ///
+    pub fn with_type_abbreviations(
+        &mut self,
+        abbreviations: im::HashMap<(EcoString, EcoString), EcoString>,
+    ) {
+        self.type_abbreviations = abbreviations;
+    }
+
+    /// Print types that have been aliased using the name of their alias. The
+    /// table is keyed by the module of the aliased type and its structure, as
+    /// given by `alias_table_key`. Types without an alias are printed as usual.
+    ///
+    pub fn with_alias_table(&mut self, aliases: im::HashMap<(EcoString, EcoString), EcoString>) {
+        self.alias_table = aliases;
+    }
+
+    /// Expand these type aliases, keyed by module and alias name, to the type
+    /// they stand for. Only aliases without type parameters are supported.
+    ///
+    pub fn with_type_aliases(&mut self, aliases: im::HashMap<(EcoString, EcoString), Arc<Type>>) {
+        self.type_aliases = aliases;
+    }
+
+    /// Follow at most this many aliases when expanding a type alias, printing
+    /// the name of the last alias reached rather than what it stands for. By
+    /// default aliases are expanded fully.
+    ///
+    pub fn with_alias_expansion_depth(&mut self, depth: usize) {
+        self.alias_expansion_depth = Some(depth);
+    }
+
+    /// A line explaining each abbreviation that has been printed, in the order
+    /// they were first used.
+    ///
+    pub fn abbreviation_legend(&self) -> Vec<String> {
+        self.used_abbreviations
+            .iter()
+            .filter_map(|key| {
+                let abbreviation = self.type_abbreviations.get(key)?;
+                let (module, name) = key;
+                Some(format!("{abbreviation} = {module}.{name}"))
+            })
+            .collect()
+    }
+
+    /// Annotate the prelude `Int` and `Float` types with a bit width, e.g.
+    /// `Int(64)`, for when interfacing with fixed width foreign data. The
+    /// widths are keyed by type name.
+    ///
+    pub fn with_numeric_widths(&mut self, widths: im::HashMap<EcoString, u8>) {
+        self.numeric_widths = widths;
+    }
+
+    /// Keep tuples with at most this many elements on a single line, even when
+    /// the type they are in is too wide and has to be broken.
+    ///
+    pub fn with_tuple_single_line_max_elems(&mut self, max: usize) {
+        self.tuple_single_line_max_elems = Some(max);
+    }
+
+    /// Whether to print a trailing comma after the last argument when a list of
+    /// arguments is broken over multiple lines. Defaults to `true`.
+    ///
+    pub fn with_trailing_commas(&mut self, trailing_commas: bool) {
+        self.omit_trailing_commas = !trailing_commas;
+    }
+
+    /// Collapse a type that takes a single argument and is nested directly in
+    /// itself at least `min` times into one with a superscript count, e.g.
+    /// `List(List(List(Int)))` is printed as `List³(Int)`.
+    ///
+    pub fn with_repeat_wrapper_superscript(&mut self, min: usize) {
+        self.repeat_wrapper_min = Some(min);
+    }
+
+    /// Print at most this many distinct type variable names, printing any
+    /// further type variables as `_`.
+    ///
+    pub fn with_max_distinct_vars(&mut self, max: usize) {
+        self.max_distinct_vars = Some(max);
+    }
+
+    /// Print these type variables, keyed by id, as the type that inference
+    /// defaulted them to, followed by a `/* defaulted */` mark so that hovers
+    /// show where a default was chosen.
+    ///
+    /// The defaults are given here because a type variable that has been
+    /// linked to another type no longer records its id.
+    ///
+    pub fn with_defaulted_marks(&mut self, defaulted: im::HashMap<u64, Arc<Type>>) {
+        self.defaulted_vars = defaulted;
+    }
+
+    /// Print type variables as their raw ids, e.g. `#2231`, so they can be
+    /// matched up with the ids in type inference logs when debugging.
+    ///
+    pub fn with_raw_var_ids(&mut self) {
+        self.raw_var_ids = true;
+    }
+
+    /// Set how new names are chosen for type variables.
+    ///
+    pub fn with_var_naming(&mut self, scheme: VarNaming) {
+        self.var_naming = scheme;
+    }
+
+    /// Set how unbound type variables are printed.
+    ///
+    pub fn with_unbound_rendering(&mut self, style: UnboundStyle) {
+        self.unbound_style = style;
+    }
+
+    /// Surround type and module names that are not valid Gleam identifiers
+    /// with backticks, so output involving types from foreign interop is
+    /// unambiguous.
+    ///
+    pub fn with_quoted_unusual_names(&mut self) {
+        self.quote_unusual_names = true;
+    }
+
+    /// Qualify a named type with its package as well as its module, e.g.
+    /// `other_package:wibble.Wibble`, when a type with the same module and
+    /// type name from another package has already been printed.
+    ///
+    pub fn with_package_qualification(&mut self) {
+        self.printed_packages = Some(im::HashMap::new());
+    }
+
+    /// Always qualify named types with their full module path, e.g.
+    /// `gleam.Int` or `gleam/option.Option`, so the output does not depend on
+    /// what is imported or which module it is read in.
+    ///
+    pub fn with_full_qualification(&mut self) {
+        self.full_qualification = true;
+    }
+
+    /// Always qualify prelude types, e.g. `gleam.Int`, wherever they appear
+    /// in the type. Other types are qualified as usual.
+    ///
+    pub fn with_explicit_prelude(&mut self) {
+        self.explicit_prelude = true;
+    }
+
+    /// Never qualify prelude types, even when a type of the same name from
+    /// another module has been printed, matching how they are written in an
+    /// annotation. Full qualification and an explicit prelude still apply.
+    ///
+    pub fn with_bare_prelude(&mut self) {
+        self.bare_prelude = true;
+    }
+
+    /// End the output of `pretty_print` with a line saying which names are type
+    /// parameters, e.g. `-- where a, b are type parameters`, for snippets read
+    /// on their own.
+    ///
+    pub fn with_var_legend(&mut self) {
+        self.var_legend = true;
+    }
+
+    /// Apply a transformation to every string produced by `pretty_print`,
+    /// such as escaping for a particular editor.
+    ///
+    pub fn with_post_processor(&mut self, post_processor: PostProcessor) {
+        self.post_processor = DebugIgnore(Some(post_processor));
+    }
+
+    /// The type names that have had to be qualified so far because a type of
+    /// the same name from a different module was also printed, in
+    /// alphabetical order.
+    ///
+    pub fn ambiguous_names(&self) -> Vec<EcoString> {
+        self.ambiguous_names.iter().cloned().sorted().collect()
+    }
+
     /// Render a Type as a well formatted string.
     ///
+    /// A type variable is given the same name everywhere it appears, both
+    /// within the type and in any other type printed with this printer, until
+    /// `reset_var_counter` is called.
+    ///
     pub fn pretty_print(&mut self, typ: &Type, initial_indent: usize) -> String {
+        let mut rendered = self.render(typ, initial_indent, DEFAULT_MAX_WIDTH);
+        if self.var_legend {
+            match self.legend_vars.as_slice() {
+                [] => (),
+                [var] => rendered.push_str(&format!("\n-- where {var} is a type parameter")),
+                vars => rendered.push_str(&format!(
+                    "\n-- where {} are type parameters",
+                    vars.iter().join(", ")
+                )),
+            }
+        }
+        self.post_process(rendered)
+    }
+
+    /// Render a Type as a well formatted string, wrapping lines that would be
+    /// wider than `max_width`.
+    ///
+    pub fn pretty_print_width(
+        &mut self,
+        typ: &Type,
+        initial_indent: usize,
+        max_width: isize,
+    ) -> String {
+        let rendered = self.render(typ, initial_indent, max_width);
+        self.post_process(rendered)
+    }
+
+    /// Apply the post processor set with `with_post_processor`, if any.
+    fn post_process(&self, rendered: String) -> String {
+        match self.post_processor.as_ref() {
+            Some(post_processor) => post_processor(rendered),
+            None => rendered,
+        }
+    }
+
+    fn render(&mut self, typ: &Type, initial_indent: usize, max_width: isize) -> String {
+        self.legend_vars.clear();
         let mut buffer = String::with_capacity(initial_indent);
         for _ in 0..initial_indent {
             buffer.push(' ');
         }
+        self.record_clashing_names(typ);
         buffer
             .to_doc()
             .append(self.print(typ))
             .nest(initial_indent as isize)
-            .to_pretty_string(80)
+            .to_pretty_string(max_width)
+    }
+
+    /// Render the signature of a custom type constructor as a function, e.g.
+    /// `fn(Int, String) -> MyType`, as a hover shows it.
+    ///
+    pub fn print_constructor(&mut self, arg_types: &[Arc<Type>], return_type: &Type) -> String {
+        let constructor = Type::Fn {
+            args: arg_types.to_vec(),
+            retrn: Arc::new(return_type.clone()),
+        };
+        self.pretty_print_width(&constructor, 0, DEFAULT_MAX_WIDTH)
+    }
+
+    /// The number of columns the longest line of the type would take up when
+    /// printed by `pretty_print`, without building the printed string.
+    ///
+    pub fn printed_width(&mut self, typ: &Type) -> usize {
+        let mut columns = ColumnCounter::default();
+        self.record_clashing_names(typ);
+        self.print(typ)
+            .pretty_print(DEFAULT_MAX_WIDTH, &mut columns)
+            .expect("counting columns cannot fail");
+        columns.widest
+    }
+
+    /// Print a type as a document that owns all of its text, so it can outlive
+    /// both the type and the printer.
+    ///
+    pub fn print_owned(&mut self, typ: &Type) -> Document<'static> {
+        self.print(typ)
     }
 
-    // TODO: have this function return a Document that borrows from the Type.
-    // Is this possible? The lifetime would have to go through the Arc<Refcell<Type>>
-    // for TypeVar::Link'd types.
+    // A document borrowing from the Type would have to borrow through the
+    // Arc<RefCell<TypeVar>> of linked type variables, so it could only ever
+    // borrow for the non-Link cases. It wouldn't save anything: every name in a
+    // Type is an EcoString and is cloned into a `Document::EcoString` without
+    // allocating, so the documents built here are cheap already.
     pub fn print<'a>(&mut self, typ: &Type) -> Document<'a> {
         match typ {
             Type::Named {
-                name, args, module, ..
+                package,
+                name,
+                args,
+                module,
+                ..
             } => {
-                let doc = if self.name_clashes_if_unqualified(name, module) {
-                    qualify_type_name(module, name)
+                let alias = if self.alias_table.is_empty() {
+                    None
                 } else {
-                    let _ = self.printed_types.insert(name.clone(), module.clone());
-                    name.to_doc()
+                    alias_table_key(typ).and_then(|key| self.alias_table.get(&key).cloned())
                 };
-                if args.is_empty() {
-                    doc
-                } else {
-                    doc.append("(")
-                        .append(self.args_to_gleam_doc(args))
-                        .append(")")
+
+                if alias.is_none() {
+                    if let Some(aliased) = self.expandable_alias(module, name, args) {
+                        let key = (module.clone(), name.clone());
+                        let _ = self.expanding_aliases.insert(key.clone());
+                        self.alias_hops += 1;
+                        let doc = self.print(&aliased);
+                        self.alias_hops -= 1;
+                        let _ = self.expanding_aliases.remove(&key);
+                        return doc;
+                    }
+                }
+
+                let doc = match alias {
+                    Some(alias) => self.token(TokenKind::TypeName, alias),
+                    None => self.named_type_doc(package, module, name, args),
+                };
+                self.mark(Marked::NamedType(module.clone(), name.clone()), doc)
+            }
+
+            Type::Fn { args, retrn } => self.fn_doc(args, &[], retrn),
+
+            Type::Var { type_: typ, .. } => {
+                // A well formed type never links back to itself, but a bug in
+                // inference could produce one and we don't want to overflow
+                // the stack trying to print it.
+                let address = Arc::as_ptr(typ) as usize;
+                if !self.visiting_vars.insert(address) {
+                    return self.token(TokenKind::Annotation, "<recursive>");
                 }
+                let doc = self.type_var_doc(&typ.borrow());
+                let _ = self.visiting_vars.remove(&address);
+                doc
             }
 
-            Type::Fn { args, retrn } => "fn("
-                .to_doc()
-                .append(self.args_to_gleam_doc(args))
-                .append(") ->")
-                .append(
-                    break_("", " ")
-                        .append(self.print(retrn))
-                        .nest(INDENT)
-                        .group(),
-                ),
+            Type::Tuple { elems, .. } if matches!(self.tuple_single_line_max_elems, Some(max) if elems.len() <= max) =>
+            {
+                let single_line = std::mem::replace(&mut self.single_line, true);
+                let doc = self.tuple_doc(elems);
+                self.single_line = single_line;
+                doc.group()
+            }
 
-            Type::Var { type_: typ, .. } => self.type_var_doc(&typ.borrow()),
+            Type::Tuple { elems, .. } => self.tuple_doc(elems),
+        }
+    }
 
-            Type::Tuple { elems, .. } => self.args_to_gleam_doc(elems).surround("#(", ")"),
+    /// Print a type the same as `print`, except that if it is a function its
+    /// arguments are printed with the given labels, e.g.
+    /// `fn(first: Int, second: Bool) -> Bool`. Arguments without a label are
+    /// printed as just their type.
+    ///
+    pub fn print_with_labels<'a>(
+        &mut self,
+        typ: &Type,
+        labels: &[Option<EcoString>],
+    ) -> Document<'a> {
+        match typ.fn_types() {
+            Some((args, retrn)) => self.fn_doc(&args, labels, &retrn),
+            None => self.print(typ),
         }
     }
 
-    fn name_clashes_if_unqualified(&mut self, type_: &EcoString, module: &str) -> bool {
-        match self.printed_types.get(type_) {
-            None => false,
-            Some(previous_module) if module == previous_module => false,
-            Some(_different_module) => true,
+    /// Print just the head of a type along with how many arguments it takes,
+    /// e.g. `Result/2`, `fn/1` or `#/3`, without printing the arguments
+    /// themselves. Type variables are printed by name.
+    ///
+    pub fn print_head(&mut self, type_: &Type) -> EcoString {
+        let type_ = collapse_links(Arc::new(type_.clone()), &mut HashSet::new());
+        match type_.as_ref() {
+            Type::Named {
+                package,
+                module,
+                name,
+                args,
+                ..
+            } => {
+                let head = self
+                    .type_name_doc(package, module, name)
+                    .to_pretty_string(isize::MAX);
+                eco_format!("{head}/{}", args.len())
+            }
+            Type::Fn { args, .. } => eco_format!("fn/{}", args.len()),
+            Type::Tuple { elems, .. } => eco_format!("#/{}", elems.len()),
+            // Either an unlinked type variable or one linked back to itself,
+            // which is printed as `<recursive>`.
+            Type::Var { .. } => self.print(&type_).to_pretty_string(isize::MAX).into(),
         }
     }
 
-    fn type_var_doc<'a>(&mut self, typ: &TypeVar) -> Document<'a> {
+    /// Print a type the same as `print`, except that if it is a named type its
+    /// arguments are printed with the given field labels, e.g.
+    /// `User(name: String, age: Int)`, one per line if they don't fit on one.
+    ///
+    pub fn print_with_field_labels<'a>(
+        &mut self,
+        typ: &Type,
+        field_labels: Option<&[EcoString]>,
+    ) -> Document<'a> {
+        match (typ, field_labels) {
+            (
+                Type::Named {
+                    package,
+                    module,
+                    name,
+                    args,
+                    ..
+                },
+                Some(labels),
+            ) => {
+                let labels = labels.iter().cloned().map(Some).collect_vec();
+                self.named_doc(package, module, name, args, &labels, &[])
+            }
+            _ => self.print(typ),
+        }
+    }
+
+    /// Print a type the same as `print`, except that if it is a named type the
+    /// arguments for its phantom type parameters, the ones flagged in
+    /// `phantom`, are marked as such, e.g. `Tagged(phantom a, Int)`.
+    ///
+    pub fn print_with_phantom_params<'a>(&mut self, typ: &Type, phantom: &[bool]) -> Document<'a> {
         match typ {
-            TypeVar::Link { type_: ref typ, .. } => self.print(typ),
-            TypeVar::Unbound { id, .. } | TypeVar::Generic { id, .. } => self.generic_type_var(*id),
+            Type::Named {
+                package,
+                module,
+                name,
+                args,
+                ..
+            } => self.named_doc(package, module, name, args, &[], phantom),
+            _ => self.print(typ),
         }
     }
 
-    pub fn generic_type_var<'a>(&mut self, id: u64) -> Document<'a> {
-        match self.names.get(&id) {
-            Some(n) => {
-                let _ = self.printed_types.insert(n.clone(), "".into());
-                n.to_doc()
+    fn named_doc(
+        &mut self,
+        package: &EcoString,
+        module: &EcoString,
+        name: &EcoString,
+        args: &[Arc<Type>],
+        labels: &[Option<EcoString>],
+        phantom: &[bool],
+    ) -> Document<'static> {
+        let doc = self.type_name_doc(package, module, name);
+        let doc = match self.numeric_widths.get(name) {
+            Some(width) if is_prelude_module(module) && is_numeric(name) => doc.append(self.token(
+                TokenKind::Annotation,
+                Document::String(format!("({width})")),
+            )),
+            Some(_) | None => doc,
+        };
+        if args.is_empty() {
+            doc
+        } else {
+            docvec![
+                doc,
+                self.token(TokenKind::Punctuation, "("),
+                self.args_to_gleam_doc(args, labels, phantom),
+                self.token(TokenKind::Punctuation, ")"),
+            ]
+        }
+    }
+
+    fn named_type_doc(
+        &mut self,
+        package: &EcoString,
+        module: &EcoString,
+        name: &EcoString,
+        args: &[Arc<Type>],
+    ) -> Document<'static> {
+        if let (Some(min), [arg]) = (self.repeat_wrapper_min, args) {
+            let (count, innermost) = wrapper_repetitions(module, name, arg);
+            if count >= min && count > 1 {
+                // The wrappers that are left out are still part of the path
+                // to the innermost type.
+                let depth = self.node_path.len();
+                self.node_path.resize(depth + count - 1, 0);
+                let innermost = self.args_to_gleam_doc(&[innermost], &[], &[]);
+                self.node_path.truncate(depth);
+                return docvec![
+                    self.type_name_doc(package, module, name),
+                    self.token(TokenKind::Annotation, Document::String(superscript(count))),
+                    self.token(TokenKind::Punctuation, "("),
+                    innermost,
+                    self.token(TokenKind::Punctuation, ")"),
+                ];
             }
-            None => {
-                let n = self.next_letter();
-                let _ = self.names.insert(id, n.clone());
-                let _ = self.printed_types.insert(n.clone(), "".into());
-                n.to_doc()
+        }
+
+        self.named_doc(package, module, name, args, &[], &[])
+    }
+
+    fn tuple_doc(&mut self, elems: &[Arc<Type>]) -> Document<'static> {
+        docvec![
+            self.token(TokenKind::Punctuation, "#("),
+            self.args_to_gleam_doc(elems, &[], &[]),
+            self.token(TokenKind::Punctuation, ")"),
+        ]
+    }
+
+    fn fn_doc(
+        &mut self,
+        args: &[Arc<Type>],
+        labels: &[Option<EcoString>],
+        retrn: &Type,
+    ) -> Document<'static> {
+        docvec![
+            self.token(TokenKind::Keyword, "fn"),
+            self.token(TokenKind::Punctuation, "("),
+            self.args_to_gleam_doc(args, labels, &[]),
+            self.token(TokenKind::Punctuation, ")"),
+            " ",
+            self.token(TokenKind::Punctuation, "->"),
+        ]
+        .append(
+            self.break_("", " ")
+                .append(self.print_child(args.len(), retrn))
+                .nest(INDENT)
+                .group(),
+        )
+    }
+
+    /// A break, unless the document being printed has to stay on a single line
+    /// in which case it is always rendered unbroken.
+    fn break_(&self, broken: &'static str, unbroken: &'static str) -> Document<'static> {
+        if self.single_line {
+            unbroken.to_doc()
+        } else {
+            break_(broken, unbroken)
+        }
+    }
+
+    /// Print the child of a type at the given argument index.
+    fn print_child(&mut self, index: usize, typ: &Type) -> Document<'static> {
+        self.node_path.push(index);
+        let doc = if matches!(self.truncation_depth, Some(max) if self.depth >= max) {
+            self.token(TokenKind::Annotation, "…")
+        } else {
+            self.depth += 1;
+            let doc = self.print(typ);
+            self.depth -= 1;
+            doc
+        };
+        let doc = self.mark(Marked::Node(self.node_path.clone()), doc);
+        let _ = self.node_path.pop();
+        doc
+    }
+
+    /// Visit the type a type variable is linked to. The variables being
+    /// followed are kept in `visiting_vars`, as they are by `print`, so that a
+    /// variable linked back to itself is not followed forever.
+    fn follow_link<T>(
+        &mut self,
+        var: &Arc<RefCell<TypeVar>>,
+        visit: impl FnOnce(&mut Self, &Type) -> T,
+    ) -> FollowedLink<T> {
+        let linked = match &*var.borrow() {
+            TypeVar::Link { type_ } => type_.clone(),
+            TypeVar::Unbound { .. } | TypeVar::Generic { .. } => return FollowedLink::Unlinked,
+        };
+        let address = Arc::as_ptr(var) as usize;
+        if !self.visiting_vars.insert(address) {
+            return FollowedLink::Recursive;
+        }
+        let visited = visit(self, &linked);
+        let _ = self.visiting_vars.remove(&address);
+        FollowedLink::Followed(visited)
+    }
+
+    /// Surround a document with marks saying what it is, if the type is being
+    /// rendered with `render_marked`.
+    fn mark<'a>(&mut self, marked: Marked, doc: Document<'a>) -> Document<'a> {
+        match &mut self.marks {
+            Some(marks) => {
+                let id = marks.len();
+                marks.push(marked);
+                docvec![Document::Mark(id), doc, Document::Mark(id)]
             }
+            None => doc,
         }
     }
 
-    fn next_letter(&mut self) -> EcoString {
-        let alphabet_length = 26;
-        let char_offset = 97;
-        let mut chars = vec![];
-        let mut n;
-        let mut rest = self.uid;
+    fn token<'a>(&mut self, kind: TokenKind, doc: impl Documentable<'a>) -> Document<'a> {
+        self.mark(Marked::Token(kind), doc.to_doc())
+    }
 
-        loop {
-            n = rest % alphabet_length;
-            rest /= alphabet_length;
-            chars.push((n as u8 + char_offset) as char);
+    /// Render a type along with the byte range of everything that was marked
+    /// while printing it, in the order the ranges end.
+    fn render_marked(&mut self, type_: &Type) -> (String, Vec<(Range<usize>, Marked)>) {
+        self.record_clashing_names(type_);
+        let outer_marks = self.marks.replace(vec![]);
+        let doc = self.print(type_);
+        let doc = self.mark(Marked::Node(vec![]), doc);
+        let marked = std::mem::replace(&mut self.marks, outer_marks).unwrap_or_default();
 
-            if rest == 0 {
-                break;
+        let (rendered, offsets) = doc.to_pretty_string_with_marks(DEFAULT_MAX_WIDTH);
+        let mut starts = HashMap::new();
+        let mut ranges = vec![];
+        for (id, offset) in offsets {
+            // Each mark is placed twice, first where the marked document
+            // starts and then where it ends.
+            match starts.remove(&id) {
+                None => {
+                    let _ = starts.insert(id, offset);
+                }
+                Some(start) => {
+                    if let Some(marked) = marked.get(id) {
+                        ranges.push((start..offset, marked.clone()));
+                    }
+                }
             }
-            rest -= 1
         }
+        (rendered, ranges)
+    }
 
-        self.uid += 1;
-        chars.into_iter().rev().collect()
+    /// The classified pieces of a rendered type, in order. Whitespace is not
+    /// part of any piece, so pieces that are only whitespace, such as a
+    /// trailing comma that wasn't printed, are left out.
+    fn render_tokens(&mut self, type_: &Type) -> (String, Vec<(Range<usize>, TokenKind)>) {
+        let (rendered, marked) = self.render_marked(type_);
+        let tokens = marked
+            .into_iter()
+            .filter_map(|(range, marked)| match marked {
+                Marked::Token(kind) => Some((trim_range(&rendered, range)?, kind)),
+                Marked::NamedType(..) | Marked::Node(_) => None,
+            })
+            .sorted_by_key(|(range, _)| range.start)
+            .collect();
+        (rendered, tokens)
     }
 
-    fn args_to_gleam_doc(&mut self, args: &[Arc<Type>]) -> Document<'static> {
-        if args.is_empty() {
-            return nil();
+    fn expandable_alias(
+        &self,
+        module: &EcoString,
+        name: &EcoString,
+        args: &[Arc<Type>],
+    ) -> Option<Arc<Type>> {
+        let key = (module.clone(), name.clone());
+        if !args.is_empty()
+            || matches!(self.alias_expansion_depth, Some(depth) if self.alias_hops >= depth)
+            || self.expanding_aliases.contains(&key)
+        {
+            return None;
         }
+        self.type_aliases.get(&key).cloned()
+    }
 
-        let args = join(
-            args.iter().map(|t| self.print(t).group()),
-            break_(",", ", "),
-        );
-        break_("", "")
-            .append(args)
-            .nest(INDENT)
-            .append(break_(",", ""))
-            .group()
+    /// Render a type as it would be written in an annotation in the given
+    /// module, along with the imports needed to use it there.
+    ///
+    /// If the printer was given the names in scope in that module with
+    /// `with_type_names` they are respected, so aliased imports are used.
+    ///
+    pub fn copy_ready(&mut self, type_: &Type, current_module: &str) -> CopyPayload {
+        let names = match &self.type_names {
+            Some(names) if names.current_module() == current_module => names.clone(),
+            Some(_) | None => TypeNames::new(current_module.into()),
+        };
+        let outer_names = self.type_names.replace(names);
+        let annotation = self.render(type_, 0, DEFAULT_MAX_WIDTH);
+        self.type_names = outer_names;
+
+        let import_qualifiers = std::mem::take(&mut self.import_qualifiers);
+        let imports = std::mem::take(&mut self.referenced_modules)
+            .into_iter()
+            .sorted()
+            .map(|module| match import_qualifiers.get(&module) {
+                Some(qualifier) if qualifier != module_basename(&module) => {
+                    format!("import {module} as {qualifier}")
+                }
+                Some(_) | None => format!("import {module}"),
+            })
+            .collect();
+
+        CopyPayload {
+            annotation,
+            imports,
+        }
     }
-}
 
-fn qualify_type_name(module: &str, type_name: &str) -> Document<'static> {
-    let type_name = Document::String(type_name.to_string());
-    docvec![Document::String(module.to_string()), ".", type_name]
-}
+    /// Render a type, annotating function types with the given effects after
+    /// their return type, e.g. `fn(Int) -> Bool !{io}`.
+    ///
+    /// Gleam does not track effects yet, this exists so that the annotation
+    /// can be supported without restructuring the printer when it does.
+    ///
+    pub fn print_fn_with_effects(&mut self, type_: &Type, effects: Option<EcoString>) -> String {
+        let rendered = self.render(type_, 0, DEFAULT_MAX_WIDTH);
+        match effects {
+            Some(effects) if type_.fn_types().is_some() => format!("{rendered} !{{{effects}}}"),
+            Some(_) | None => rendered,
+        }
+    }
 
-#[test]
-fn next_letter_test() {
-    let mut printer = Printer::new();
-    assert_eq!(printer.next_letter().as_str(), "a");
-    assert_eq!(printer.next_letter().as_str(), "b");
-    assert_eq!(printer.next_letter().as_str(), "c");
-    assert_eq!(printer.next_letter().as_str(), "d");
-    assert_eq!(printer.next_letter().as_str(), "e");
-    assert_eq!(printer.next_letter().as_str(), "f");
-    assert_eq!(printer.next_letter().as_str(), "g");
-    assert_eq!(printer.next_letter().as_str(), "h");
-    assert_eq!(printer.next_letter().as_str(), "i");
-    assert_eq!(printer.next_letter().as_str(), "j");
-    assert_eq!(printer.next_letter().as_str(), "k");
-    assert_eq!(printer.next_letter().as_str(), "l");
-    assert_eq!(printer.next_letter().as_str(), "m");
-    assert_eq!(printer.next_letter().as_str(), "n");
-    assert_eq!(printer.next_letter().as_str(), "o");
-    assert_eq!(printer.next_letter().as_str(), "p");
-    assert_eq!(printer.next_letter().as_str(), "q");
-    assert_eq!(printer.next_letter().as_str(), "r");
-    assert_eq!(printer.next_letter().as_str(), "s");
-    assert_eq!(printer.next_letter().as_str(), "t");
-    assert_eq!(printer.next_letter().as_str(), "u");
-    assert_eq!(printer.next_letter().as_str(), "v");
-    assert_eq!(printer.next_letter().as_str(), "w");
-    assert_eq!(printer.next_letter().as_str(), "x");
-    assert_eq!(printer.next_letter().as_str(), "y");
-    assert_eq!(printer.next_letter().as_str(), "z");
-    assert_eq!(printer.next_letter().as_str(), "aa");
-    assert_eq!(printer.next_letter().as_str(), "ab");
-    assert_eq!(printer.next_letter().as_str(), "ac");
-    assert_eq!(printer.next_letter().as_str(), "ad");
-    assert_eq!(printer.next_letter().as_str(), "ae");
-    assert_eq!(printer.next_letter().as_str(), "af");
+    /// Render the type of a partially typed expression, printing the type
+    /// variables with the given ids as holes (`_`) rather than as letters.
+    ///
+    pub fn print_with_holes(&mut self, type_: &Type, hole_ids: &im::HashSet<u64>) -> String {
+        self.hole_ids = hole_ids.clone();
+        let rendered = self.pretty_print(type_, 0);
+        self.hole_ids = im::HashSet::new();
+        rendered
+    }
+
+    /// Render a type along with a stable anchor id (`module.Name`) for each
+    /// named type in it, keyed by the byte range the type occupies in the
+    /// output. The range of a type with arguments includes the arguments, so
+    /// the ranges of nested types overlap.
+    ///
+    pub fn print_with_anchors(&mut self, type_: &Type) -> (String, Vec<(Range<usize>, EcoString)>) {
+        let (rendered, marked) = self.render_marked(type_);
+        let anchors = marked
+            .into_iter()
+            .filter_map(|(range, marked)| match marked {
+                Marked::NamedType(module, name) => Some((range, eco_format!("{module}.{name}"))),
+                Marked::Token(_) | Marked::Node(_) => None,
+            })
+            .sorted_by_key(|(range, _)| range.start)
+            .collect();
+        (rendered, anchors)
+    }
+
+    /// Render a type the same as `pretty_print`, with every line of the
+    /// output prefixed with the given gutter, such as `// `. The gutter counts
+    /// towards the line width.
+    ///
+    pub fn pretty_print_gutter(
+        &mut self,
+        type_: &Type,
+        initial_indent: usize,
+        gutter: &str,
+    ) -> String {
+        let width = DEFAULT_MAX_WIDTH.saturating_sub(gutter.chars().count() as isize);
+        self.pretty_print_width(type_, initial_indent, width)
+            .lines()
+            .map(|line| format!("{gutter}{line}"))
+            .join("\n")
+    }
+
+    /// Render a type as a canonical flat key for search indexing, e.g.
+    /// `fn(gleam.Int,gleam.Bool)->gleam.Bool`.
+    ///
+    /// Named types are always qualified with their full module name, there is
+    /// no whitespace, and type variables are named in the order they appear so
+    /// the key does not depend on what any printer has previously printed.
+    /// None of the settings of a printer apply, so that the same type always
+    /// has the same key.
+    ///
+    pub fn print_index_key(type_: &Type) -> String {
+        let mut key = String::new();
+        Printer::new().write_index_key(type_, &mut key);
+        key
+    }
+
+    fn write_index_key(&mut self, type_: &Type, key: &mut String) {
+        match type_ {
+            Type::Named {
+                module, name, args, ..
+            } => {
+                key.push_str(module);
+                key.push('.');
+                key.push_str(name);
+                if !args.is_empty() {
+                    key.push('(');
+                    self.write_index_keys(args, key);
+                    key.push(')');
+                }
+            }
+
+            Type::Fn { args, retrn } => {
+                key.push_str("fn(");
+                self.write_index_keys(args, key);
+                key.push_str(")->");
+                self.write_index_key(retrn, key);
+            }
+
+            Type::Var { type_: var } => {
+                match self.follow_link(var, |printer, type_| printer.write_index_key(type_, key)) {
+                    FollowedLink::Followed(()) => (),
+                    FollowedLink::Recursive => key.push_str("<recursive>"),
+                    FollowedLink::Unlinked => {
+                        let Some(id) = var_id(&var.borrow()) else {
+                            return;
+                        };
+                        let name = match self.names.get(&id) {
+                            Some(name) => name.clone(),
+                            None => {
+                                let name = self.next_letter();
+                                let _ = self.names.insert(id, name.clone());
+                                name
+                            }
+                        };
+                        key.push_str(&name);
+                    }
+                }
+            }
+
+            Type::Tuple { elems } => {
+                key.push_str("#(");
+                self.write_index_keys(elems, key);
+                key.push(')');
+            }
+        }
+    }
+
+    fn write_index_keys(&mut self, types: &[Arc<Type>], key: &mut String) {
+        for (i, type_) in types.iter().enumerate() {
+            if i > 0 {
+                key.push(',');
+            }
+            self.write_index_key(type_, key);
+        }
+    }
+
+    /// Render a change from one type to another as a single `before → after`
+    /// line, with the parts that changed surrounded by `*`. Useful to preview
+    /// a refactoring that changes a function's signature.
+    ///
+    pub fn print_refactor_preview(&mut self, old: &Type, new: &Type) -> String {
+        self.record_clashing_names(old);
+        self.record_clashing_names(new);
+        let (old, new) = self.diff_docs(old, new);
+        docvec![old, " → ", new].to_pretty_string(isize::MAX)
+    }
+
+    /// Describe how two types differ as JSON, for tools that want to present
+    /// a mismatch themselves.
+    ///
+    /// Where the two types have the same shape the result mirrors it, with a
+    /// `tag` of `named`, `fn`, `tuple`, or `var`, and the parts that differ
+    /// are `mismatch` nodes holding the `expected` and `got` types.
+    ///
+    pub fn diff_json(&mut self, expected: &Type, got: &Type) -> serde_json::Value {
+        if let Type::Var { type_: var } = expected {
+            if let FollowedLink::Followed(diff) =
+                self.follow_link(var, |printer, expected| printer.diff_json(expected, got))
+            {
+                return diff;
+            }
+        }
+        if let Type::Var { type_: var } = got {
+            if let FollowedLink::Followed(diff) =
+                self.follow_link(var, |printer, got| printer.diff_json(expected, got))
+            {
+                return diff;
+            }
+        }
+
+        match (expected, got) {
+            (
+                Type::Named {
+                    module, name, args, ..
+                },
+                Type::Named {
+                    module: got_module,
+                    name: got_name,
+                    args: got_args,
+                    ..
+                },
+            ) if module == got_module && name == got_name && args.len() == got_args.len() => {
+                json!({
+                    "tag": "named",
+                    "module": module.as_str(),
+                    "name": name.as_str(),
+                    "args": self.diff_args_json(args, got_args),
+                })
+            }
+
+            (
+                Type::Fn { args, retrn },
+                Type::Fn {
+                    args: got_args,
+                    retrn: got_retrn,
+                },
+            ) if args.len() == got_args.len() => json!({
+                "tag": "fn",
+                "args": self.diff_args_json(args, got_args),
+                "return": self.diff_json(retrn, got_retrn),
+            }),
+
+            (Type::Tuple { elems }, Type::Tuple { elems: got_elems })
+                if elems.len() == got_elems.len() =>
+            {
+                json!({
+                    "tag": "tuple",
+                    "elems": self.diff_args_json(elems, got_elems),
+                })
+            }
+
+            (Type::Var { type_ }, Type::Var { type_: got_type })
+                if var_id(&type_.borrow()) == var_id(&got_type.borrow()) =>
+            {
+                self.type_json(expected)
+            }
+
+            (Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } | Type::Var { .. }, _) => {
+                json!({
+                    "tag": "mismatch",
+                    "expected": self.type_json(expected),
+                    "got": self.type_json(got),
+                })
+            }
+        }
+    }
+
+    fn diff_args_json(
+        &mut self,
+        expected: &[Arc<Type>],
+        got: &[Arc<Type>],
+    ) -> Vec<serde_json::Value> {
+        expected
+            .iter()
+            .zip(got)
+            .map(|(expected, got)| self.diff_json(expected, got))
+            .collect()
+    }
+
+    /// Render a report of how the fields of a record changed between two
+    /// versions, one line per field that changed:
+    ///
+    /// ```text
+    /// ~ age: Int → Float
+    /// - nickname: String
+    /// + email: String
+    /// ```
+    ///
+    /// Removed and retyped fields come first, in their old order, followed by
+    /// the added fields in their new order. A field whose type only differs in
+    /// the names of its type variables has not changed.
+    ///
+    pub fn print_record_migration(
+        &mut self,
+        old_fields: &[(EcoString, Arc<Type>)],
+        new_fields: &[(EcoString, Arc<Type>)],
+    ) -> String {
+        let find = |fields: &[(EcoString, Arc<Type>)], label: &EcoString| {
+            fields
+                .iter()
+                .find(|(other, _)| other == label)
+                .map(|(_, type_)| type_.clone())
+        };
+
+        let mut lines = vec![];
+        for (label, old) in old_fields {
+            match find(new_fields, label) {
+                None => lines.push(format!("- {label}: {}", self.print_inline(old))),
+                Some(new) if !alpha_equivalent(old, &new) => lines.push(format!(
+                    "~ {label}: {} → {}",
+                    self.print_inline(old),
+                    self.print_inline(&new)
+                )),
+                Some(_) => (),
+            }
+        }
+        for (label, new) in new_fields {
+            if find(old_fields, label).is_none() {
+                lines.push(format!("+ {label}: {}", self.print_inline(new)));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Render one line per entry of an API comparison for a CI summary, each
+    /// starting with a marker for how the entry changed. Entries that changed
+    /// show the old and new types with the differences surrounded by `*`.
+    ///
+    /// ```text
+    /// ✓ unchanged wibble: fn(Int) -> Int
+    /// ✗ broken wobble: fn(*Int*) -> Int → fn(*String*) -> Int
+    /// ```
+    ///
+    pub fn print_compat_summary(&mut self, entries: &[(EcoString, Type, Type, Compat)]) -> String {
+        entries
+            .iter()
+            .map(|(name, old, new, compat)| match compat {
+                Compat::Unchanged => format!("✓ unchanged {name}: {}", self.print_inline(new)),
+                Compat::Widened => {
+                    format!(
+                        "⚠ widened {name}: {}",
+                        self.print_refactor_preview(old, new)
+                    )
+                }
+                Compat::Broken => {
+                    format!("✗ broken {name}: {}", self.print_refactor_preview(old, new))
+                }
+            })
+            .join("\n")
+    }
+
+    /// Render two types side by side, surrounding the parts of each that differ
+    /// from the other with `*`.
+    ///
+    fn diff_docs(&mut self, old: &Type, new: &Type) -> (Document<'static>, Document<'static>) {
+        if let Type::Var { type_: var } = old {
+            if let FollowedLink::Followed(diff) =
+                self.follow_link(var, |printer, old| printer.diff_docs(old, new))
+            {
+                return diff;
+            }
+        }
+        if let Type::Var { type_: var } = new {
+            if let FollowedLink::Followed(diff) =
+                self.follow_link(var, |printer, new| printer.diff_docs(old, new))
+            {
+                return diff;
+            }
+        }
+
+        match (old, new) {
+            (
+                Type::Named {
+                    package,
+                    module,
+                    name,
+                    args,
+                    ..
+                },
+                Type::Named {
+                    package: new_package,
+                    module: new_module,
+                    name: new_name,
+                    args: new_args,
+                    ..
+                },
+            ) if module == new_module && name == new_name && args.len() == new_args.len() => {
+                let old_name = self.type_name_doc(package, module, name);
+                let new_name = self.type_name_doc(new_package, new_module, new_name);
+                if args.is_empty() {
+                    return (old_name, new_name);
+                }
+                let (old_args, new_args) = self.diff_args_docs(args, new_args);
+                (
+                    old_name.append(old_args.surround("(", ")")),
+                    new_name.append(new_args.surround("(", ")")),
+                )
+            }
+
+            (
+                Type::Fn { args, retrn },
+                Type::Fn {
+                    args: new_args,
+                    retrn: new_retrn,
+                },
+            ) if args.len() == new_args.len() => {
+                let (old_args, new_args) = self.diff_args_docs(args, new_args);
+                let (old_retrn, new_retrn) = self.diff_docs(retrn, new_retrn);
+                (
+                    docvec!["fn(", old_args, ") -> ", old_retrn],
+                    docvec!["fn(", new_args, ") -> ", new_retrn],
+                )
+            }
+
+            (Type::Tuple { elems }, Type::Tuple { elems: new_elems })
+                if elems.len() == new_elems.len() =>
+            {
+                let (old_elems, new_elems) = self.diff_args_docs(elems, new_elems);
+                (old_elems.surround("#(", ")"), new_elems.surround("#(", ")"))
+            }
+
+            (Type::Var { type_ }, Type::Var { type_: new_type })
+                if var_id(&type_.borrow()) == var_id(&new_type.borrow()) =>
+            {
+                (self.print(old), self.print(new))
+            }
+
+            (Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } | Type::Var { .. }, _) => (
+                self.print(old).surround("*", "*"),
+                self.print(new).surround("*", "*"),
+            ),
+        }
+    }
+
+    fn diff_args_docs(
+        &mut self,
+        old: &[Arc<Type>],
+        new: &[Arc<Type>],
+    ) -> (Document<'static>, Document<'static>) {
+        let (old, new): (Vec<_>, Vec<_>) = old
+            .iter()
+            .zip(new)
+            .map(|(old, new)| self.diff_docs(old, new))
+            .unzip();
+        (join(old, ", ".to_doc()), join(new, ", ".to_doc()))
+    }
+
+    /// Render the current version of a type on a single line with the parts
+    /// that are the same as in the baseline version replaced by `…`, so the
+    /// parts that changed stand out, e.g. `fn(…, …) -> String`.
+    ///
+    pub fn print_changes_from(&mut self, baseline: &Type, current: &Type) -> String {
+        self.record_clashing_names(baseline);
+        self.record_clashing_names(current);
+        self.changes_doc(baseline, current)
+            .unwrap_or_else(|| "…".to_doc())
+            .to_pretty_string(isize::MAX)
+    }
+
+    /// The parts of the current type that differ from the baseline, or `None`
+    /// if they are the same.
+    ///
+    fn changes_doc(&mut self, baseline: &Type, current: &Type) -> Option<Document<'static>> {
+        if let Type::Var { type_: var } = baseline {
+            if let FollowedLink::Followed(diff) = self.follow_link(var, |printer, baseline| {
+                printer.changes_doc(baseline, current)
+            }) {
+                return diff;
+            }
+        }
+        if let Type::Var { type_: var } = current {
+            if let FollowedLink::Followed(diff) = self.follow_link(var, |printer, current| {
+                printer.changes_doc(baseline, current)
+            }) {
+                return diff;
+            }
+        }
+
+        match (baseline, current) {
+            (
+                Type::Named {
+                    package,
+                    module,
+                    name,
+                    args,
+                    ..
+                },
+                Type::Named {
+                    module: current_module,
+                    name: current_name,
+                    args: current_args,
+                    ..
+                },
+            ) if module == current_module
+                && name == current_name
+                && args.len() == current_args.len() =>
+            {
+                let args = self.changes_args_doc(args, current_args)?;
+                Some(
+                    self.type_name_doc(package, module, name)
+                        .append(args.surround("(", ")")),
+                )
+            }
+
+            (
+                Type::Fn { args, retrn },
+                Type::Fn {
+                    args: current_args,
+                    retrn: current_retrn,
+                },
+            ) if args.len() == current_args.len() => {
+                let args = self.changes_args_doc(args, current_args);
+                let retrn = self.changes_doc(retrn, current_retrn);
+                if args.is_none() && retrn.is_none() {
+                    return None;
+                }
+                Some(docvec![
+                    "fn(",
+                    args.unwrap_or_else(|| join(
+                        current_args.iter().map(|_| "…".to_doc()),
+                        ", ".to_doc()
+                    )),
+                    ") -> ",
+                    retrn.unwrap_or_else(|| "…".to_doc())
+                ])
+            }
+
+            (
+                Type::Tuple { elems },
+                Type::Tuple {
+                    elems: current_elems,
+                },
+            ) if elems.len() == current_elems.len() => {
+                let elems = self.changes_args_doc(elems, current_elems)?;
+                Some(elems.surround("#(", ")"))
+            }
+
+            (
+                Type::Var { type_ },
+                Type::Var {
+                    type_: current_type,
+                },
+            ) if var_id(&type_.borrow()) == var_id(&current_type.borrow()) => None,
+
+            (Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } | Type::Var { .. }, _) => {
+                Some(self.print(current))
+            }
+        }
+    }
+
+    fn changes_args_doc(
+        &mut self,
+        baseline: &[Arc<Type>],
+        current: &[Arc<Type>],
+    ) -> Option<Document<'static>> {
+        let changes = baseline
+            .iter()
+            .zip(current)
+            .map(|(baseline, current)| self.changes_doc(baseline, current))
+            .collect_vec();
+        if changes.iter().all(Option::is_none) {
+            return None;
+        }
+        let changes = changes
+            .into_iter()
+            .map(|change| change.unwrap_or_else(|| "…".to_doc()));
+        Some(join(changes, ", ".to_doc()))
+    }
+
+    /// Render each parameter of a function type on its own line, as
+    /// `label: Type`, followed by a final `-> Type` line for the return type.
+    /// This suits UIs that present parameters as a vertical form.
+    ///
+    /// Parameters without a label are given a positional name (`arg1`, ...).
+    /// Types that are not functions are rendered as a single line.
+    ///
+    pub fn print_parameter_lines(
+        &mut self,
+        labels: &[Option<EcoString>],
+        type_: &Type,
+    ) -> Vec<String> {
+        let Some((args, retrn)) = type_.fn_types() else {
+            return vec![self.print_inline(type_)];
+        };
+        let mut lines = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let arg = self.print_inline(arg);
+                match labels.get(i) {
+                    Some(Some(label)) => format!("{label}: {arg}"),
+                    Some(None) | None => format!("arg{}: {arg}", i + 1),
+                }
+            })
+            .collect_vec();
+        lines.push(format!("-> {}", self.print_inline(&retrn)));
+        lines
+    }
+
+    /// Render a function type on a single line with its argument labels,
+    /// marking the labelled parameters that are optional as `label?: Type`.
+    ///
+    /// `optional` runs parallel to `labels`; parameters missing from it are
+    /// required. Unlabelled parameters are rendered as just their type, and
+    /// types that are not functions are rendered as they are.
+    ///
+    pub fn print_with_optional_labels(
+        &mut self,
+        labels: &[Option<EcoString>],
+        optional: &[bool],
+        type_: &Type,
+    ) -> String {
+        let Some((args, retrn)) = type_.fn_types() else {
+            return self.print_inline(type_);
+        };
+        let args = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let arg = self.print_inline(arg);
+                match labels.get(i) {
+                    Some(Some(label)) if optional.get(i) == Some(&true) => {
+                        format!("{label}?: {arg}")
+                    }
+                    Some(Some(label)) => format!("{label}: {arg}"),
+                    Some(None) | None => arg,
+                }
+            })
+            .join(", ");
+        format!("fn({args}) -> {}", self.print_inline(&retrn))
+    }
+
+    /// Render a type as it would be written in an annotation if it were moved
+    /// into the module the given names are in scope in. Types from that module
+    /// are no longer qualified while types from the module they came from may
+    /// need to be.
+    ///
+    /// Unlike `with_type_names` this leaves the printer as it was, so it can
+    /// go on printing types for the module they came from.
+    ///
+    pub fn print_as_if_in_module(&mut self, type_: &Type, target_names: TypeNames) -> String {
+        let outer_names = self.type_names.replace(target_names);
+        let referenced_modules = std::mem::take(&mut self.referenced_modules);
+        let import_qualifiers = std::mem::take(&mut self.import_qualifiers);
+        let rendered = self.render(type_, 0, DEFAULT_MAX_WIDTH);
+        self.import_qualifiers = import_qualifiers;
+        self.referenced_modules = referenced_modules;
+        self.type_names = outer_names;
+        rendered
+    }
+
+    /// Render a type along with every position in it that a structural
+    /// editor can target: the slots for arguments, tuple elements, and return
+    /// types, and the module names, type names, type variables, and holes
+    /// within them. The placeholders are ordered by where they start, with a
+    /// slot coming before the names inside it.
+    ///
+    pub fn print_structural(&mut self, type_: &Type) -> (String, Vec<Placeholder>) {
+        let (rendered, marked) = self.render_marked(type_);
+        let placeholders = marked
+            .into_iter()
+            .filter_map(|(range, marked)| {
+                let kind = match marked {
+                    Marked::Node(path) if path.is_empty() => return None,
+                    Marked::Node(_) => PlaceholderKind::Slot,
+                    Marked::Token(kind) => placeholder_kind(&rendered, &range, kind)?,
+                    Marked::NamedType(..) => return None,
+                };
+                let range = trim_range(&rendered, range)?;
+                Some(Placeholder { kind, range })
+            })
+            // Slots end after the names inside them, so they must be moved
+            // before them.
+            .sorted_by_key(|placeholder| {
+                (
+                    placeholder.range.start,
+                    placeholder.kind != PlaceholderKind::Slot,
+                )
+            })
+            .collect();
+        (rendered, placeholders)
+    }
+
+    /// Find the subtype that is rendered at the given byte offset of the
+    /// output of `pretty_print`, for click-to-locate in interactive hovers.
+    ///
+    /// The subtype is identified by its path from the root, where each step
+    /// is the index of an argument, tuple element, or type argument. The
+    /// return type of a function comes after its arguments. Offsets that are
+    /// not within the rendered type return `None`.
+    ///
+    pub fn path_to_offset(&mut self, type_: &Type, byte_offset: usize) -> Option<Vec<usize>> {
+        let (_, marked) = self.render_marked(type_);
+        marked
+            .into_iter()
+            .filter_map(|(range, marked)| match marked {
+                Marked::Node(path) if range.contains(&byte_offset) => Some(path),
+                Marked::Node(_) | Marked::Token(_) | Marked::NamedType(..) => None,
+            })
+            .max_by_key(|path| path.len())
+    }
+
+    /// Render a type on a single line, shortening any type or module name
+    /// longer than `max_name_len` characters by replacing its middle with an
+    /// ellipsis, e.g. `SomeVery…LongName`, so both ends stay recognisable.
+    ///
+    pub fn print_middle_ellipsis(&mut self, type_: &Type, max_name_len: usize) -> String {
+        self.max_name_length = Some(max_name_len);
+        let rendered = self.print_inline(type_);
+        self.max_name_length = None;
+        rendered
+    }
+
+    /// Describe a type as a linear phrase with little punctuation, suited to
+    /// being read aloud by a screen reader, e.g. `function taking Int and Bool
+    /// returning Nil` or `list of tuple of String and a`.
+    ///
+    pub fn print_screen_reader(&mut self, type_: &Type) -> String {
+        match type_ {
+            Type::Named {
+                module, name, args, ..
+            } if is_prelude_module(module) && name == "List" && args.len() == 1 => {
+                format!("list of {}", self.screen_reader_list(args))
+            }
+
+            Type::Named {
+                package,
+                module,
+                name,
+                args,
+                ..
+            } => {
+                let name = self
+                    .type_name_doc(package, module, name)
+                    .to_pretty_string(isize::MAX);
+                if args.is_empty() {
+                    name
+                } else {
+                    format!("{name} of {}", self.screen_reader_list(args))
+                }
+            }
+
+            Type::Fn { args, retrn } => {
+                let args = if args.is_empty() {
+                    "nothing".into()
+                } else {
+                    self.screen_reader_list(args)
+                };
+                let retrn = self.print_screen_reader(retrn);
+                format!("function taking {args} returning {retrn}")
+            }
+
+            Type::Var { type_: var } => {
+                match self.follow_link(var, |printer, type_| printer.print_screen_reader(type_)) {
+                    FollowedLink::Followed(description) => description,
+                    FollowedLink::Recursive => "recursive".into(),
+                    FollowedLink::Unlinked => self.print_inline(type_),
+                }
+            }
+
+            Type::Tuple { elems } if elems.is_empty() => "empty tuple".into(),
+
+            Type::Tuple { elems } => format!("tuple of {}", self.screen_reader_list(elems)),
+        }
+    }
+
+    /// Join the descriptions of some types as `A`, `A and B`, or
+    /// `A, B and C`.
+    ///
+    fn screen_reader_list(&mut self, types: &[Arc<Type>]) -> String {
+        let mut descriptions = types
+            .iter()
+            .map(|type_| self.print_screen_reader(type_))
+            .collect_vec();
+        match descriptions.pop() {
+            Some(last) if descriptions.is_empty() => last,
+            Some(last) => format!("{} and {last}", descriptions.join(", ")),
+            None => String::new(),
+        }
+    }
+
+    /// Describe a function or value for a machine readable API manifest, as
+    /// JSON containing its name, its labelled parameter types, and its return
+    /// type. Values that are not functions have a single `type` instead.
+    ///
+    pub fn print_manifest_entry(
+        &mut self,
+        name: &str,
+        labels: &[Option<EcoString>],
+        type_: &Type,
+    ) -> serde_json::Value {
+        let Some((args, retrn)) = type_.fn_types() else {
+            return json!({ "name": name, "type": self.type_json(type_) });
+        };
+        let parameters = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let label = labels.get(i).cloned().flatten().map(String::from);
+                json!({ "label": label, "type": self.type_json(arg) })
+            })
+            .collect_vec();
+        json!({
+            "name": name,
+            "parameters": parameters,
+            "return": self.type_json(&retrn),
+        })
+    }
+
+    /// Describe a type as JSON for tools that would rather not parse the
+    /// printed form. Each part of the type has a `tag` of `named`, `fn`,
+    /// `tuple`, or `var`, along with its module, name, and arguments as
+    /// appropriate, and a `display` field holding how that part is printed.
+    ///
+    pub fn print_json(&mut self, type_: &Type) -> serde_json::Value {
+        self.json_display = true;
+        let json = self.type_json(type_);
+        self.json_display = false;
+        json
+    }
+
+    fn type_json(&mut self, type_: &Type) -> serde_json::Value {
+        let mut json = match type_ {
+            Type::Named {
+                module, name, args, ..
+            } => json!({
+                "tag": "named",
+                "module": module.as_str(),
+                "name": name.as_str(),
+                "args": args.iter().map(|arg| self.type_json(arg)).collect_vec(),
+            }),
+
+            Type::Fn { args, retrn } => json!({
+                "tag": "fn",
+                "args": args.iter().map(|arg| self.type_json(arg)).collect_vec(),
+                "return": self.type_json(retrn),
+            }),
+
+            Type::Var { type_: var } => {
+                match self.follow_link(var, |printer, type_| printer.type_json(type_)) {
+                    FollowedLink::Followed(json) => return json,
+                    FollowedLink::Recursive => json!({ "kind": "recursive" }),
+                    FollowedLink::Unlinked => json!({
+                        "tag": "var",
+                        "name": self.print_inline(type_),
+                    }),
+                }
+            }
+
+            Type::Tuple { elems } => json!({
+                "tag": "tuple",
+                "elems": elems.iter().map(|elem| self.type_json(elem)).collect_vec(),
+            }),
+        };
+
+        if self.json_display {
+            let display = self.print_inline(type_);
+            if let Some(object) = json.as_object_mut() {
+                let _ = object.insert("display".into(), display.into());
+            }
+        }
+        json
+    }
+
+    /// Render a type on a single line with each type variable followed by the
+    /// number of times it appears, e.g. `fn(a×2, a×2) -> b×1`, to show which
+    /// variables are used once and which are shared.
+    ///
+    pub fn print_with_var_counts(&mut self, type_: &Type) -> String {
+        count_vars(type_, &mut self.var_counts);
+        let rendered = self.print_inline(type_);
+        self.var_counts.clear();
+        rendered
+    }
+
+    /// Render a type on a single line of at most `max_chars` characters, for
+    /// previews in hovers. Types that are too long have their most deeply
+    /// nested arguments replaced with `…`, e.g. `#(Int, #(…, …))`, keeping as
+    /// much of the outer structure as will fit.
+    ///
+    /// If even the outermost type is too long it is cut short with an `…`,
+    /// never in the middle of a character.
+    ///
+    pub fn pretty_print_truncated(&mut self, type_: &Type, max_chars: usize) -> String {
+        let full = self.print_inline(type_);
+        if full.chars().count() <= max_chars {
+            return full;
+        }
+
+        let mut best = None;
+        // Each extra level of depth makes the output longer, and once it
+        // reaches the full depth of the type the output is the complete type
+        // which we already know is too long, so this loop ends.
+        for depth in 0.. {
+            self.truncation_depth = Some(depth);
+            let rendered = self.print_inline(type_);
+            self.truncation_depth = None;
+            if rendered.chars().count() > max_chars {
+                break;
+            }
+            best = Some(rendered);
+        }
+
+        // When not even the ellipsis fits there is nothing to show.
+        best.unwrap_or_else(|| match max_chars.checked_sub(1) {
+            Some(kept) => {
+                let mut shortened: String = full.chars().take(kept).collect();
+                shortened.push('…');
+                shortened
+            }
+            None => String::new(),
+        })
+    }
+
+    /// Render a type on a single line however long it is, e.g. for a log line.
+    /// No legend is added, even if one has been asked for with
+    /// `with_var_legend`, as it would go on a line of its own.
+    ///
+    pub fn pretty_print_single_line(&mut self, type_: &Type) -> String {
+        self.print_inline(type_)
+    }
+
+    /// The narrowest width at which a type can be rendered without any line
+    /// breaks, i.e. the length of its single line form.
+    ///
+    pub fn min_inline_width(&mut self, type_: &Type) -> usize {
+        self.print_inline(type_).chars().count()
+    }
+
+    /// Render a type on a single line, however long it is.
+    fn print_inline(&mut self, type_: &Type) -> String {
+        self.record_clashing_names(type_);
+        self.print(type_).group().to_pretty_string(isize::MAX)
+    }
+
+    /// Render a compact single line signature for listing in a fuzzy finder,
+    /// e.g. `name(Int, Bool) Nil` for a function or `name Int` for a value.
+    ///
+    pub fn print_palette_entry(&mut self, name: &str, type_: &Type) -> String {
+        let Some((args, retrn)) = type_.fn_types() else {
+            return format!("{name} {}", self.print_inline(type_));
+        };
+        let args = args.iter().map(|arg| self.print_inline(arg)).join(", ");
+        format!("{name}({args}) {}", self.print_inline(&retrn))
+    }
+
+    /// Render the arguments of a function type as an LSP snippet with a
+    /// numbered tabstop for each argument's name, e.g.
+    /// `${1:arg1}: Int, ${2:arg2}: Bool`.
+    ///
+    /// Types that are not functions have no arguments and produce an empty
+    /// snippet.
+    ///
+    pub fn print_snippet(&mut self, type_: &Type) -> String {
+        let Some((args, _)) = type_.fn_types() else {
+            return String::new();
+        };
+        args.iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let position = i + 1;
+                format!("${{{position}:arg{position}}}: {}", self.print_inline(arg))
+            })
+            .join(", ")
+    }
+
+    /// Render a type along with the byte range of each piece of the output
+    /// and what kind of piece it is, so a client can highlight it without
+    /// having to parse it. Whitespace, including the line breaks added when
+    /// the type is too long for one line, is not part of any piece.
+    ///
+    pub fn print_spans(&mut self, type_: &Type) -> (String, Vec<(Range<usize>, TokenKind)>) {
+        self.render_tokens(type_)
+    }
+
+    /// Render a type and classify the pieces of the output as LSP semantic
+    /// tokens, so hover content can be highlighted by the client.
+    ///
+    /// The token types index into `TYPE_SEMANTIC_TOKEN_LEGEND`.
+    ///
+    pub fn semantic_tokens(&mut self, type_: &Type) -> Vec<SemanticToken> {
+        let (rendered, pieces) = self.render_tokens(type_);
+        let mut tokens = vec![];
+        let mut previous_line = 0;
+        let mut previous_start = 0;
+
+        for (range, kind) in pieces {
+            let token_type = match kind {
+                TokenKind::Keyword => 0,
+                TokenKind::TypeVar => 1,
+                TokenKind::TypeName => 2,
+                TokenKind::Module => 3,
+                TokenKind::Label => 4,
+                TokenKind::Annotation => 5,
+                TokenKind::Punctuation => continue,
+            };
+
+            let before = rendered.get(..range.start).unwrap_or_default();
+            let line = before.matches('\n').count() as u32;
+            let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let start = utf16_length(before.get(line_start..).unwrap_or_default());
+            let length = utf16_length(rendered.get(range).unwrap_or_default());
+
+            let delta_line = line - previous_line;
+            let delta_start = if delta_line == 0 {
+                start - previous_start
+            } else {
+                start
+            };
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type,
+                token_modifiers_bitset: 0,
+            });
+            previous_line = line;
+            previous_start = start;
+        }
+
+        tokens
+    }
+
+    /// Render a literal for the simplest value of a type, for use in generated
+    /// tests and documentation.
+    ///
+    /// Only prelude types and tuples can be handled as the constructors of
+    /// custom types are not available in `Type`. For anything else `None` is
+    /// returned.
+    ///
+    pub fn print_default_value(&mut self, type_: &Type) -> Option<String> {
+        match type_ {
+            Type::Named { module, name, .. } if is_prelude_module(module) => {
+                let value = match name.as_str() {
+                    "Int" => "0",
+                    "Float" => "0.0",
+                    "String" => "\"\"",
+                    "Bool" => "False",
+                    "Nil" => "Nil",
+                    "List" => "[]",
+                    "BitArray" => "<<>>",
+                    _ => return None,
+                };
+                Some(value.into())
+            }
+
+            Type::Named { .. } | Type::Fn { .. } => None,
+
+            Type::Var { type_: var } => {
+                match self.follow_link(var, |printer, type_| printer.print_default_value(type_)) {
+                    FollowedLink::Followed(value) => value,
+                    FollowedLink::Recursive | FollowedLink::Unlinked => None,
+                }
+            }
+
+            Type::Tuple { elems } => {
+                let elems = elems
+                    .iter()
+                    .map(|elem| self.print_default_value(elem))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("#({})", elems.join(", ")))
+            }
+        }
+    }
+
+    /// Render the top level constructors a value of a type can be built with,
+    /// as patterns, for exhaustiveness tooling.
+    ///
+    /// Only prelude types and tuples can be handled as the constructors of
+    /// custom types are not available in `Type`. Types with an unbounded
+    /// number of constructors, such as `Int`, return `None`.
+    ///
+    pub fn print_discriminators(&mut self, type_: &Type) -> Option<Vec<String>> {
+        match type_ {
+            Type::Named { module, name, .. } if is_prelude_module(module) => {
+                let discriminators: &[&str] = match name.as_str() {
+                    "Bool" => &["True", "False"],
+                    "Nil" => &["Nil"],
+                    "Result" => &["Ok(_)", "Error(_)"],
+                    "List" => &["[]", "[_, ..]"],
+                    _ => return None,
+                };
+                Some(discriminators.iter().map(|d| d.to_string()).collect())
+            }
+
+            Type::Named { .. } | Type::Fn { .. } => None,
+
+            Type::Var { type_: var } => {
+                match self.follow_link(var, |printer, type_| printer.print_discriminators(type_)) {
+                    FollowedLink::Followed(value) => value,
+                    FollowedLink::Recursive | FollowedLink::Unlinked => None,
+                }
+            }
+
+            Type::Tuple { elems } => {
+                let shape = elems.iter().map(|_| "_").join(", ");
+                Some(vec![format!("#({shape})")])
+            }
+        }
+    }
+
+    /// The name to print for a named type, qualified if need be.
+    fn type_name_doc(
+        &mut self,
+        package: &EcoString,
+        module: &EcoString,
+        name: &EcoString,
+    ) -> Document<'static> {
+        let key = (module.clone(), name.clone());
+        if let Some(abbreviation) = self.type_abbreviations.get(&key) {
+            if !self.used_abbreviations.contains(&key) {
+                self.used_abbreviations.push(key);
+            }
+            return self.token(TokenKind::TypeName, abbreviation.clone());
+        }
+
+        let (qualifier, name) = match self.local_type_name(module, name) {
+            Some(local_name) => (None, local_name),
+            None => (self.qualifier(package, module, name), name.clone()),
+        };
+        let name = self.shorten_if_long(&name);
+        let name = self.quote_if_unusual(&name, is_bare_type_name);
+        match qualifier {
+            Some(qualifier) => {
+                let qualifier = self.shorten_if_long(&qualifier);
+                let qualifier = self.quote_if_unusual(&qualifier, is_bare_module_name);
+                docvec![
+                    self.token(TokenKind::Module, qualifier),
+                    self.token(TokenKind::Punctuation, "."),
+                    self.token(TokenKind::TypeName, name),
+                ]
+            }
+            None => self.token(TokenKind::TypeName, name),
+        }
+    }
+
+    /// The name a type is imported unqualified as into the module of
+    /// `type_names`, unless in strict mode it clashes with a type from that
+    /// module.
+    fn local_type_name(&self, module: &EcoString, name: &EcoString) -> Option<EcoString> {
+        let names = self.type_names.as_ref()?;
+        match names.named_type(module, name) {
+            NamedTypeNames::Unqualified(local_name)
+                if module == names.current_module()
+                    || !self.clashing_names.contains(local_name) =>
+            {
+                Some(local_name.into())
+            }
+            NamedTypeNames::Unqualified(_)
+            | NamedTypeNames::Qualified(..)
+            | NamedTypeNames::Unimported(_) => None,
+        }
+    }
+
+    /// The qualifier to print a named type with, if it needs one.
+    fn qualifier(
+        &mut self,
+        package: &EcoString,
+        module: &EcoString,
+        name: &EcoString,
+    ) -> Option<EcoString> {
+        let is_prelude = is_prelude_type(module, name);
+        if self.full_qualification || (self.explicit_prelude && is_prelude) {
+            return Some(module.clone());
+        }
+
+        if let Some(names) = &self.type_names {
+            if is_prelude || module == names.current_module() {
+                return None;
+            }
+            if let Some(alias) = names.imported_module_alias(module) {
+                return Some(alias.clone());
+            }
+            let _ = self.referenced_modules.insert(module.clone());
+            return match self.import_qualifiers.get(module) {
+                Some(qualifier) => Some(qualifier.clone()),
+                None => Some(module_basename(module).into()),
+            };
+        }
+
+        if let Some(printed_packages) = &mut self.printed_packages {
+            let key = (module.clone(), name.clone());
+            match printed_packages.get(&key) {
+                Some(printed) if printed != package => {
+                    let _ = self.ambiguous_names.insert(name.clone());
+                    return Some(eco_format!("{package}:{module}"));
+                }
+                Some(_) => (),
+                None => {
+                    let _ = printed_packages.insert(key, package.clone());
+                }
+            }
+        }
+
+        let _ = self.printed_modules.insert(module.clone());
+        if self.bare_prelude && is_prelude {
+            return None;
+        }
+        if self.clashing_names.contains(name) || self.name_clashes_if_unqualified(name, module) {
+            let _ = self.ambiguous_names.insert(name.clone());
+            Some(self.module_qualifier(module).into())
+        } else {
+            let _ = self.printed_types.insert(name.clone(), module.clone());
+            None
+        }
+    }
+
+    fn shorten_if_long(&self, name: &EcoString) -> EcoString {
+        match self.max_name_length {
+            Some(max) if name.chars().count() > max => middle_ellipsis(name, max),
+            Some(_) | None => name.clone(),
+        }
+    }
+
+    fn quote_if_unusual(&self, name: &EcoString, is_bare: fn(&str) -> bool) -> EcoString {
+        if self.quote_unusual_names && !is_bare(name) {
+            eco_format!("`{name}`")
+        } else {
+            name.clone()
+        }
+    }
+
+    /// The qualifier to use for a type from the given module: the last segment
+    /// of the module name, as it would be written when imported, unless
+    /// another module with the same last segment has been printed, in which
+    /// case the full module name is used.
+    ///
+    fn module_qualifier<'b>(&'b self, module: &'b EcoString) -> &'b str {
+        if let Some(qualifier) = self.qualifier_overrides.get(module) {
+            return qualifier;
+        }
+        let basename = module_basename(module);
+        let basename_is_ambiguous = self
+            .printed_modules
+            .iter()
+            .any(|other| other != module && module_basename(other) == basename);
+        if basename_is_ambiguous {
+            module
+        } else {
+            basename
+        }
+    }
+
+    /// When printing with qualifier overrides, make sure that a type name used
+    /// from several modules is never printed bare. Otherwise the first one
+    /// printed would be unqualified even though its module is displayed with a
+    /// different name.
+    ///
+    /// In strict mode, when printing a type as it would be written in a
+    /// module, the same goes for a type imported unqualified into the module
+    /// when a type of the same name from the module is also printed. The
+    /// name it is recorded under is the one it is imported as.
+    ///
+    /// When printing a type as it would be written in a module, this also
+    /// decides the qualifier for each module that would need importing, as
+    /// two modules with the same last segment can't both be imported under
+    /// it. Otherwise every module in the type is recorded as printed up
+    /// front, so a qualifier is not shortened to a last segment that a module
+    /// later in the type shares.
+    ///
+    fn record_clashing_names(&mut self, type_: &Type) {
+        if let Some(names) = &self.type_names {
+            let current_module = names.current_module();
+            for (module, name) in named_type_occurrences(type_) {
+                if module == *current_module
+                    || is_prelude_type(&module, &name)
+                    || names.imported_module_alias(&module).is_some()
+                    || self.import_qualifiers.contains_key(&module)
+                {
+                    continue;
+                }
+                let basename = module_basename(&module);
+                let qualifier_is_taken = |qualifier: &str| {
+                    self.import_qualifiers
+                        .values()
+                        .any(|taken| taken == qualifier)
+                        || names.module_alias_is_taken(qualifier)
+                };
+                let qualifier = if qualifier_is_taken(basename) {
+                    module.replace("/", "_")
+                } else {
+                    basename.into()
+                };
+                let _ = self.import_qualifiers.insert(module, qualifier);
+            }
+
+            if self.strict {
+                let current_module = names.current_module();
+                let named_types = named_type_occurrences(type_).collect_vec();
+                for (module, name) in &named_types {
+                    if module == current_module {
+                        continue;
+                    }
+                    if let NamedTypeNames::Unqualified(local_name) = names.named_type(module, name)
+                    {
+                        if named_types.iter().any(|(other_module, other_name)| {
+                            other_module == current_module && other_name == local_name
+                        }) {
+                            let _ = self.clashing_names.insert(local_name.into());
+                        }
+                    }
+                }
+            }
+            return;
+        }
+        for (module, _) in named_type_occurrences(type_) {
+            let _ = self.printed_modules.insert(module);
+        }
+        if self.qualifier_overrides.is_empty() {
+            return;
+        }
+        let named_types = named_type_occurrences(type_).collect_vec();
+        for (module, name) in &named_types {
+            if named_types
+                .iter()
+                .any(|(other_module, other_name)| other_name == name && other_module != module)
+            {
+                let _ = self.clashing_names.insert(name.clone());
+            }
+        }
+    }
+
+    fn name_clashes_if_unqualified(&mut self, type_: &EcoString, module: &str) -> bool {
+        match self.printed_types.get(type_) {
+            None => false,
+            Some(previous_module) if module == previous_module => false,
+            Some(_different_module) => true,
+        }
+    }
+
+    fn type_var_doc<'a>(&mut self, typ: &TypeVar) -> Document<'a> {
+        if let TypeVar::Unbound { id } | TypeVar::Generic { id } = typ {
+            if let Some(default) = self.defaulted_vars.get(id).cloned() {
+                return docvec![
+                    self.print(&default),
+                    " ",
+                    self.token(TokenKind::Annotation, "/* defaulted */"),
+                ];
+            }
+        }
+
+        match typ {
+            TypeVar::Link { type_: ref typ, .. } => self.print(typ),
+            TypeVar::Unbound { id, .. } | TypeVar::Generic { id, .. } if self.raw_var_ids => {
+                self.token(TokenKind::TypeVar, Document::String(format!("#{id}")))
+            }
+            TypeVar::Unbound { id, .. } | TypeVar::Generic { id, .. }
+                if self.hole_ids.contains(id) =>
+            {
+                self.token(TokenKind::TypeVar, "_")
+            }
+            TypeVar::Unbound { .. } if self.unbound_style == UnboundStyle::Question => {
+                self.token(TokenKind::TypeVar, "?")
+            }
+            TypeVar::Unbound { id, .. } | TypeVar::Generic { id, .. } => {
+                let doc = self.generic_type_var(*id);
+                let doc = self.token(TokenKind::TypeVar, doc);
+                match self.var_counts.get(id) {
+                    Some(count) => doc.append(
+                        self.token(TokenKind::Annotation, Document::String(format!("×{count}"))),
+                    ),
+                    None => doc,
+                }
+            }
+        }
+    }
+
+    pub fn generic_type_var<'a>(&mut self, id: u64) -> Document<'a> {
+        let name = self.var_name(id);
+        if self.var_legend && name != "_" && !self.legend_vars.contains(&name) {
+            self.legend_vars.push(name.clone());
+        }
+        name.to_doc()
+    }
+
+    fn var_name(&mut self, id: u64) -> EcoString {
+        if let Some(names) = &self.type_names {
+            if let Some(n) = names.type_variable_in_scope_name(id) {
+                let n = n.clone();
+                let _ = self.printed_types.insert(n.clone(), "".into());
+                return n;
+            }
+        }
+        match self.names.get(&id) {
+            Some(n) => {
+                let _ = self.printed_types.insert(n.clone(), "".into());
+                n.clone()
+            }
+            None if matches!(self.max_distinct_vars, Some(max) if self.names.len() >= max) => {
+                "_".into()
+            }
+            None => {
+                let mut n = self.next_var_name();
+                while self.names.values().any(|taken| *taken == n)
+                    || self
+                        .type_names
+                        .as_ref()
+                        .is_some_and(|names| names.type_variable_name_is_taken(&n))
+                {
+                    n = self.next_var_name();
+                }
+                let _ = self.names.insert(id, n.clone());
+                let _ = self.printed_types.insert(n.clone(), "".into());
+                n
+            }
+        }
+    }
+
+    fn next_var_name(&mut self) -> EcoString {
+        match &self.var_naming {
+            VarNaming::Lowercase => self.next_letter(),
+            VarNaming::Uppercase => self.next_letter().to_uppercase(),
+            VarNaming::Numbered { prefix } => {
+                let name = eco_format!("{prefix}{}", self.uid);
+                self.uid += 1;
+                name
+            }
+        }
+    }
+
+    /// The next name in the sequence `a`, `b`, ..., `z`, `aa`, `ab`, ...,
+    /// skipping any that are Gleam keywords, such as `as`, `fn`, `if`, `let`,
+    /// `pub` and `use`, so the names can be used in Gleam code.
+    ///
+    fn next_letter(&mut self) -> EcoString {
+        let alphabet_length = 26;
+        let char_offset = 97;
+        let mut chars = vec![];
+        let mut n;
+        let mut rest = self.uid;
+
+        loop {
+            n = rest % alphabet_length;
+            rest /= alphabet_length;
+            chars.push((n as u8 + char_offset) as char);
+
+            if rest == 0 {
+                break;
+            }
+            rest -= 1
+        }
+
+        self.uid += 1;
+        let name: EcoString = chars.into_iter().rev().collect();
+        if str_to_keyword(&name).is_some() {
+            return self.next_letter();
+        }
+        name
+    }
+
+    /// Arguments are printed with the label at the same position in `labels`,
+    /// and marked as phantom if the flag at the same position in `phantom` is
+    /// set. Arguments past the end of either have no label and aren't phantom.
+    fn args_to_gleam_doc(
+        &mut self,
+        args: &[Arc<Type>],
+        labels: &[Option<EcoString>],
+        phantom: &[bool],
+    ) -> Document<'static> {
+        if args.is_empty() {
+            return nil();
+        }
+
+        let mut docs = Vec::with_capacity(args.len() * 2);
+        for (i, t) in args.iter().enumerate() {
+            if i > 0 {
+                let separator = self.break_(",", ", ");
+                docs.push(self.token(TokenKind::Punctuation, separator));
+            }
+            let doc = self.print_child(i, t).group();
+            let doc = match phantom.get(i) {
+                Some(true) => docvec![self.token(TokenKind::Annotation, "phantom"), " ", doc],
+                Some(false) | None => doc,
+            };
+            docs.push(match labels.get(i) {
+                Some(Some(label)) => docvec![
+                    self.token(TokenKind::Label, label.clone()),
+                    self.token(TokenKind::Punctuation, ":"),
+                    " ",
+                    doc
+                ],
+                Some(None) | None => doc,
+            });
+        }
+        let args = concat(docs);
+        let trailing = if self.omit_trailing_commas {
+            self.break_("", "")
+        } else {
+            let trailing = self.break_(",", "");
+            self.token(TokenKind::Punctuation, trailing)
+        };
+        self.break_("", "")
+            .append(args)
+            .nest(INDENT)
+            .append(trailing)
+            .group()
+    }
+}
+
+/// A writer that keeps track of the widest line written to it rather than
+/// the text itself.
+#[derive(Debug, Default)]
+struct ColumnCounter {
+    current: usize,
+    widest: usize,
+}
+
+impl std::fmt::Write for ColumnCounter {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for char in s.chars() {
+            if char == '\n' {
+                self.current = 0;
+            } else {
+                self.current += 1;
+                self.widest = self.widest.max(self.current);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Utf8Writer for ColumnCounter {
+    fn convert_err<E: std::error::Error>(&self, error: E) -> Error {
+        Error::FileIo {
+            action: FileIoAction::WriteTo,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from("<column counter>"),
+            err: Some(error.to_string()),
+        }
+    }
+}
+
+/// Whether two types are the same up to a consistent renaming of their type
+/// variables, e.g. `fn(a) -> a` and `fn(b) -> b`.
+///
+pub fn alpha_equivalent(one: &Type, other: &Type) -> bool {
+    alpha_equivalent_vars(
+        one,
+        other,
+        &mut HashMap::new(),
+        &mut HashMap::new(),
+        &mut HashSet::new(),
+    )
+}
+
+/// Whether two types are alpha equivalent, where `comparing` holds the
+/// addresses of the pairs of types whose links are being followed. A pair
+/// that is met again is linked back to itself, and is taken to be
+/// equivalent as nothing that tells the two apart has been found so far.
+fn alpha_equivalent_vars(
+    one: &Type,
+    other: &Type,
+    one_to_other: &mut HashMap<u64, u64>,
+    other_to_one: &mut HashMap<u64, u64>,
+    comparing: &mut HashSet<(usize, usize)>,
+) -> bool {
+    let linked = |type_: &Type| match type_ {
+        Type::Var { type_ } => match &*type_.borrow() {
+            TypeVar::Link { type_ } => Some(type_.clone()),
+            TypeVar::Unbound { .. } | TypeVar::Generic { .. } => None,
+        },
+        Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } => None,
+    };
+    let (one_linked, other_linked) = (linked(one), linked(other));
+    if one_linked.is_some() || other_linked.is_some() {
+        let (one_address, other_address): (*const Type, *const Type) = (one, other);
+        let pair = (one_address as usize, other_address as usize);
+        if !comparing.insert(pair) {
+            return true;
+        }
+        let equivalent = alpha_equivalent_vars(
+            one_linked.as_deref().unwrap_or(one),
+            other_linked.as_deref().unwrap_or(other),
+            one_to_other,
+            other_to_one,
+            comparing,
+        );
+        let _ = comparing.remove(&pair);
+        return equivalent;
+    }
+
+    let mut all_equivalent = |ones: &[Arc<Type>],
+                              others: &[Arc<Type>],
+                              one_to_other: &mut HashMap<u64, u64>,
+                              other_to_one: &mut HashMap<u64, u64>| {
+        ones.len() == others.len()
+            && ones.iter().zip(others).all(|(one, other)| {
+                alpha_equivalent_vars(one, other, one_to_other, other_to_one, comparing)
+            })
+    };
+
+    match (one, other) {
+        (
+            Type::Named {
+                module, name, args, ..
+            },
+            Type::Named {
+                module: other_module,
+                name: other_name,
+                args: other_args,
+                ..
+            },
+        ) => {
+            module == other_module
+                && name == other_name
+                && all_equivalent(args, other_args, one_to_other, other_to_one)
+        }
+
+        (
+            Type::Fn { args, retrn },
+            Type::Fn {
+                args: other_args,
+                retrn: other_retrn,
+            },
+        ) => {
+            all_equivalent(args, other_args, one_to_other, other_to_one)
+                && alpha_equivalent_vars(retrn, other_retrn, one_to_other, other_to_one, comparing)
+        }
+
+        (Type::Tuple { elems }, Type::Tuple { elems: other_elems }) => {
+            all_equivalent(elems, other_elems, one_to_other, other_to_one)
+        }
+
+        (Type::Var { type_ }, Type::Var { type_: other_type }) => {
+            match (var_id(&type_.borrow()), var_id(&other_type.borrow())) {
+                (Some(id), Some(other_id)) => {
+                    *one_to_other.entry(id).or_insert(other_id) == other_id
+                        && *other_to_one.entry(other_id).or_insert(id) == id
+                }
+                _ => false,
+            }
+        }
+
+        (Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } | Type::Var { .. }, _) => false,
+    }
+}
+
+fn var_id(type_var: &TypeVar) -> Option<u64> {
+    match type_var {
+        TypeVar::Unbound { id } | TypeVar::Generic { id } => Some(*id),
+        TypeVar::Link { .. } => None,
+    }
+}
+
+/// Shorten a name to `max` characters by replacing its middle with `…`.
+fn middle_ellipsis(name: &str, max: usize) -> EcoString {
+    let Some(kept) = max.checked_sub(1) else {
+        return EcoString::new();
+    };
+    let tail_length = kept / 2;
+    let head_length = kept - tail_length;
+    let head = name.chars().take(head_length);
+    let tail = name.chars().skip(name.chars().count() - tail_length);
+    head.chain(std::iter::once('…')).chain(tail).collect()
+}
+
+fn is_bare_type_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_bare_module_name(name: &str) -> bool {
+    name.split('/').all(|segment| {
+        let mut chars = segment.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+fn is_numeric(name: &str) -> bool {
+    name == "Int" || name == "Float"
+}
+
+/// Whether a named type is one of the types defined in the prelude.
+fn is_prelude_type(module: &str, name: &str) -> bool {
+    is_prelude_module(module) && PreludeType::iter().any(|type_| type_.name() == name)
+}
+
+fn module_basename(module: &str) -> &str {
+    module.rsplit('/').next().unwrap_or(module)
+}
+
+/// The key a named type is looked up by in the table given to
+/// `Printer::with_alias_table`: the module the type is defined in, and its name
+/// followed by its fully qualified arguments, e.g.
+/// `("gleam/dict", "Dict(gleam.String, gleam.Int)")`. Types that are not named
+/// types have no key.
+///
+pub fn alias_table_key(type_: &Type) -> Option<(EcoString, EcoString)> {
+    let Type::Named {
+        module, name, args, ..
+    } = type_
+    else {
+        return None;
+    };
+    if args.is_empty() {
+        return Some((module.clone(), name.clone()));
+    }
+    let mut printer = Printer::new();
+    printer.with_full_qualification();
+    let args = args.iter().map(|arg| printer.print_inline(arg)).join(", ");
+    Some((module.clone(), eco_format!("{name}({args})")))
+}
+
+/// The module and name of each named type within a type, in the order they
+/// are printed. The types are visited as the iterator is advanced rather than
+/// all up front.
+///
+/// The names are cloned rather than borrowed as the types that type variables
+/// have been linked to can't be borrowed from the type.
+///
+pub fn named_type_occurrences(type_: &Type) -> impl Iterator<Item = (EcoString, EcoString)> + '_ {
+    NamedTypeOccurrences {
+        stack: vec![Pending::Root(type_)],
+        visiting_vars: HashSet::new(),
+    }
+}
+
+#[derive(Debug)]
+struct NamedTypeOccurrences<'a> {
+    // The types still to visit, the next one to visit being last.
+    stack: Vec<Pending<'a>>,
+    // The addresses of the linked type variables that the type being visited
+    // is within, so that a type variable linked back to itself is not visited
+    // forever. The printer prints such a type variable as `<recursive>`.
+    visiting_vars: HashSet<usize>,
+}
+
+#[derive(Debug)]
+enum Pending<'a> {
+    Root(&'a Type),
+    // The types within the root are held by the iterator rather than
+    // borrowed, as the ones reached through a linked type variable can't be.
+    Nested(Arc<Type>),
+    // All the types within the linked type variable at this address have been
+    // visited.
+    LeaveVar(usize),
+}
+
+impl Iterator for NamedTypeOccurrences<'_> {
+    type Item = (EcoString, EcoString);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(pending) = self.stack.pop() {
+            let type_ = match &pending {
+                Pending::Root(type_) => *type_,
+                Pending::Nested(type_) => type_.as_ref(),
+                Pending::LeaveVar(address) => {
+                    let _ = self.visiting_vars.remove(address);
+                    continue;
+                }
+            };
+            let occurrence = match type_ {
+                Type::Named {
+                    module, name, args, ..
+                } => {
+                    self.stack
+                        .extend(args.iter().rev().map(|arg| Pending::Nested(arg.clone())));
+                    Some((module.clone(), name.clone()))
+                }
+                Type::Fn { args, retrn } => {
+                    self.stack.push(Pending::Nested(retrn.clone()));
+                    self.stack
+                        .extend(args.iter().rev().map(|arg| Pending::Nested(arg.clone())));
+                    None
+                }
+                Type::Var { type_: var } => {
+                    let address = Arc::as_ptr(var) as usize;
+                    if let TypeVar::Link { type_ } = &*var.borrow() {
+                        if self.visiting_vars.insert(address) {
+                            self.stack.push(Pending::LeaveVar(address));
+                            self.stack.push(Pending::Nested(type_.clone()));
+                        }
+                    }
+                    None
+                }
+                Type::Tuple { elems } => {
+                    self.stack
+                        .extend(elems.iter().rev().map(|elem| Pending::Nested(elem.clone())));
+                    None
+                }
+            };
+            if occurrence.is_some() {
+                return occurrence;
+            }
+        }
+        None
+    }
+}
+
+/// How many times a type that takes a single argument is nested directly in
+/// itself, and the argument of the innermost one.
+///
+fn wrapper_repetitions(
+    module: &EcoString,
+    name: &EcoString,
+    arg: &Arc<Type>,
+) -> (usize, Arc<Type>) {
+    let mut visited_vars = HashSet::new();
+    let mut count = 1;
+    let mut arg = collapse_links(arg.clone(), &mut visited_vars);
+    loop {
+        let inner = match arg.as_ref() {
+            Type::Named {
+                module: arg_module,
+                name: arg_name,
+                args: arg_args,
+                ..
+            } if arg_module == module && arg_name == name => match arg_args.as_slice() {
+                [inner] => Some(inner.clone()),
+                _ => None,
+            },
+            Type::Named { .. } | Type::Fn { .. } | Type::Var { .. } | Type::Tuple { .. } => None,
+        };
+        match inner {
+            Some(inner) => {
+                count += 1;
+                arg = collapse_links(inner, &mut visited_vars);
+            }
+            None => return (count, arg),
+        }
+    }
+}
+
+/// Follow linked type variables to the type they are linked to. A variable
+/// that has already been visited is not followed again, as it must be linked
+/// back to itself, so it is returned instead.
+///
+fn collapse_links(type_: Arc<Type>, visited_vars: &mut HashSet<usize>) -> Arc<Type> {
+    let mut type_ = type_;
+    loop {
+        let linked = match type_.as_ref() {
+            Type::Var { type_: var } => match &*var.borrow() {
+                TypeVar::Link { type_ } if visited_vars.insert(Arc::as_ptr(var) as usize) => {
+                    Some(type_.clone())
+                }
+                TypeVar::Link { .. } | TypeVar::Unbound { .. } | TypeVar::Generic { .. } => None,
+            },
+            Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } => None,
+        };
+        match linked {
+            Some(linked) => type_ = linked,
+            None => return type_,
+        }
+    }
+}
+
+fn superscript(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .map(|digit| match digit {
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            _ => '⁹',
+        })
+        .collect()
+}
+
+fn count_vars(type_: &Type, counts: &mut HashMap<u64, usize>) {
+    count_vars_visiting(type_, counts, &mut HashSet::new())
+}
+
+/// Count the type variables in a type, where `visiting_vars` holds the
+/// addresses of the linked type variables the type is within, so that a
+/// type variable linked back to itself is not followed forever.
+fn count_vars_visiting(
+    type_: &Type,
+    counts: &mut HashMap<u64, usize>,
+    visiting_vars: &mut HashSet<usize>,
+) {
+    match type_ {
+        Type::Named { args, .. } => {
+            for arg in args {
+                count_vars_visiting(arg, counts, visiting_vars);
+            }
+        }
+        Type::Fn { args, retrn } => {
+            for arg in args {
+                count_vars_visiting(arg, counts, visiting_vars);
+            }
+            count_vars_visiting(retrn, counts, visiting_vars);
+        }
+        Type::Var { type_: var } => match *var.borrow() {
+            TypeVar::Link { ref type_ } => {
+                let address = Arc::as_ptr(var) as usize;
+                if visiting_vars.insert(address) {
+                    count_vars_visiting(type_, counts, visiting_vars);
+                    let _ = visiting_vars.remove(&address);
+                }
+            }
+            TypeVar::Unbound { id } | TypeVar::Generic { id } => {
+                *counts.entry(id).or_insert(0) += 1;
+            }
+        },
+        Type::Tuple { elems } => {
+            for elem in elems {
+                count_vars_visiting(elem, counts, visiting_vars);
+            }
+        }
+    }
+}
+
+/// What a piece of a rendered type is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// The `fn` keyword.
+    Keyword,
+    TypeVar,
+    TypeName,
+    /// A module name qualifying a type name.
+    Module,
+    Punctuation,
+    /// The label of a function argument or record field.
+    Label,
+    /// Text the printer adds that isn't part of the type, such as
+    /// `/* defaulted */`, the `×2` count of a type variable, or the `…` of a
+    /// truncated type.
+    Annotation,
+}
+
+/// What following the link of a type variable with `Printer::follow_link`
+/// gave.
+#[derive(Debug)]
+enum FollowedLink<T> {
+    /// The type variable isn't linked to a type.
+    Unlinked,
+    /// The type variable is already being followed further up the type, so
+    /// it is linked back to itself.
+    Recursive,
+    /// What visiting the type it is linked to returned.
+    Followed(T),
+}
+
+/// What a stretch of a document marked while rendering with
+/// `Printer::render_marked` is.
+#[derive(Debug, Clone)]
+enum Marked {
+    Token(TokenKind),
+    /// A named type, including its qualifier and arguments, identified by its
+    /// module and name.
+    NamedType(EcoString, EcoString),
+    /// A type or one of its children, identified by the path of argument
+    /// indices that leads to it from the root.
+    Node(Vec<usize>),
+}
+
+/// The part of a range of the rendered type that isn't whitespace, if any.
+fn trim_range(rendered: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let text = rendered.get(range.clone())?;
+    let start = range.start + (text.len() - text.trim_start().len());
+    let end = range.end - (text.len() - text.trim_end().len());
+    (start < end).then_some(start..end)
+}
+
+/// The kind of placeholder a token of a rendered type is, if it is one.
+fn placeholder_kind(
+    rendered: &str,
+    range: &Range<usize>,
+    kind: TokenKind,
+) -> Option<PlaceholderKind> {
+    match kind {
+        TokenKind::Module => Some(PlaceholderKind::Module),
+        TokenKind::TypeName => Some(PlaceholderKind::TypeName),
+        TokenKind::TypeVar if rendered.get(range.clone()) == Some("_") => {
+            Some(PlaceholderKind::Hole)
+        }
+        TokenKind::TypeVar => Some(PlaceholderKind::TypeVar),
+        TokenKind::Keyword | TokenKind::Punctuation | TokenKind::Label | TokenKind::Annotation => {
+            None
+        }
+    }
+}
+
+fn utf16_length(string: &str) -> u32 {
+    string.encode_utf16().count() as u32
+}
+
+#[test]
+fn next_letter_test() {
+    let mut printer = Printer::new();
+    assert_eq!(printer.next_letter().as_str(), "a");
+    assert_eq!(printer.next_letter().as_str(), "b");
+    assert_eq!(printer.next_letter().as_str(), "c");
+    assert_eq!(printer.next_letter().as_str(), "d");
+    assert_eq!(printer.next_letter().as_str(), "e");
+    assert_eq!(printer.next_letter().as_str(), "f");
+    assert_eq!(printer.next_letter().as_str(), "g");
+    assert_eq!(printer.next_letter().as_str(), "h");
+    assert_eq!(printer.next_letter().as_str(), "i");
+    assert_eq!(printer.next_letter().as_str(), "j");
+    assert_eq!(printer.next_letter().as_str(), "k");
+    assert_eq!(printer.next_letter().as_str(), "l");
+    assert_eq!(printer.next_letter().as_str(), "m");
+    assert_eq!(printer.next_letter().as_str(), "n");
+    assert_eq!(printer.next_letter().as_str(), "o");
+    assert_eq!(printer.next_letter().as_str(), "p");
+    assert_eq!(printer.next_letter().as_str(), "q");
+    assert_eq!(printer.next_letter().as_str(), "r");
+    assert_eq!(printer.next_letter().as_str(), "s");
+    assert_eq!(printer.next_letter().as_str(), "t");
+    assert_eq!(printer.next_letter().as_str(), "u");
+    assert_eq!(printer.next_letter().as_str(), "v");
+    assert_eq!(printer.next_letter().as_str(), "w");
+    assert_eq!(printer.next_letter().as_str(), "x");
+    assert_eq!(printer.next_letter().as_str(), "y");
+    assert_eq!(printer.next_letter().as_str(), "z");
+    assert_eq!(printer.next_letter().as_str(), "aa");
+    assert_eq!(printer.next_letter().as_str(), "ab");
+    assert_eq!(printer.next_letter().as_str(), "ac");
+    assert_eq!(printer.next_letter().as_str(), "ad");
+    assert_eq!(printer.next_letter().as_str(), "ae");
+    assert_eq!(printer.next_letter().as_str(), "af");
     assert_eq!(printer.next_letter().as_str(), "ag");
     assert_eq!(printer.next_letter().as_str(), "ah");
     assert_eq!(printer.next_letter().as_str(), "ai");
@@ -208,7 +2942,7 @@ fn next_letter_test() {
     assert_eq!(printer.next_letter().as_str(), "ap");
     assert_eq!(printer.next_letter().as_str(), "aq");
     assert_eq!(printer.next_letter().as_str(), "ar");
-    assert_eq!(printer.next_letter().as_str(), "as");
+    // `as` is a keyword
     assert_eq!(printer.next_letter().as_str(), "at");
     assert_eq!(printer.next_letter().as_str(), "au");
     assert_eq!(printer.next_letter().as_str(), "av");
@@ -245,149 +2979,1931 @@ fn next_letter_test() {
 }
 
 #[test]
-fn pretty_print_test() {
-    macro_rules! assert_string {
-        ($src:expr, $typ:expr $(,)?) => {
-            let mut printer = Printer::new();
-            assert_eq!($typ.to_string(), printer.pretty_print(&$src, 0),);
-        };
-    }
+fn pretty_print_test() {
+    macro_rules! assert_string {
+        ($src:expr, $typ:expr $(,)?) => {
+            let mut printer = Printer::new();
+            assert_eq!($typ.to_string(), printer.pretty_print(&$src, 0),);
+        };
+    }
+
+    assert_string!(
+        Type::Named {
+            module: "whatever".into(),
+            package: "whatever".into(),
+            name: "Int".into(),
+            publicity: Publicity::Public,
+            args: vec![],
+        },
+        "Int",
+    );
+    assert_string!(
+        Type::Named {
+            module: "themodule".into(),
+            package: "whatever".into(),
+            name: "Pair".into(),
+            publicity: Publicity::Public,
+            args: vec![
+                Arc::new(Type::Named {
+                    module: "whatever".into(),
+                    package: "whatever".into(),
+                    name: "Int".into(),
+                    publicity: Publicity::Public,
+                    args: vec![],
+                }),
+                Arc::new(Type::Named {
+                    module: "whatever".into(),
+                    package: "whatever".into(),
+                    name: "Bool".into(),
+                    publicity: Publicity::Public,
+                    args: vec![],
+                }),
+            ],
+        },
+        "Pair(Int, Bool)",
+    );
+    assert_string!(
+        Type::Fn {
+            args: vec![
+                Arc::new(Type::Named {
+                    args: vec![],
+                    module: "whatever".into(),
+                    package: "whatever".into(),
+                    name: "Int".into(),
+                    publicity: Publicity::Public,
+                }),
+                Arc::new(Type::Named {
+                    args: vec![],
+                    module: "whatever".into(),
+                    package: "whatever".into(),
+                    name: "Bool".into(),
+                    publicity: Publicity::Public,
+                }),
+            ],
+            retrn: Arc::new(Type::Named {
+                args: vec![],
+                module: "whatever".into(),
+                package: "whatever".into(),
+                name: "Bool".into(),
+                publicity: Publicity::Public,
+            }),
+        },
+        "fn(Int, Bool) -> Bool",
+    );
+    assert_string!(
+        Type::Var {
+            type_: Arc::new(RefCell::new(TypeVar::Link {
+                type_: Arc::new(Type::Named {
+                    args: vec![],
+                    module: "whatever".into(),
+                    package: "whatever".into(),
+                    name: "Int".into(),
+                    publicity: Publicity::Public,
+                }),
+            })),
+        },
+        "Int",
+    );
+    assert_string!(
+        Type::Var {
+            type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 2231 })),
+        },
+        "a",
+    );
+    assert_string!(
+        fn_(
+            vec![Arc::new(Type::Var {
+                type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 78 })),
+            })],
+            Arc::new(Type::Var {
+                type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 2 })),
+            }),
+        ),
+        "fn(a) -> b",
+    );
+    assert_string!(
+        fn_(
+            vec![Arc::new(Type::Var {
+                type_: Arc::new(RefCell::new(TypeVar::Generic { id: 78 })),
+            })],
+            Arc::new(Type::Var {
+                type_: Arc::new(RefCell::new(TypeVar::Generic { id: 2 })),
+            }),
+        ),
+        "fn(a) -> b",
+    );
+}
+
+#[test]
+fn function_test() {
+    assert_eq!(pretty_print(fn_(vec![], int())), "fn() -> Int");
+
+    assert_eq!(
+        pretty_print(fn_(vec![int(), int(), int()], int())),
+        "fn(Int, Int, Int) -> Int"
+    );
+
+    assert_eq!(
+        pretty_print(fn_(
+            vec![
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float()
+            ],
+            float()
+        )),
+        "fn(
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+) -> Float"
+    );
+
+    assert_eq!(
+        pretty_print(fn_(
+            vec![
+                tuple(vec![float(), float(), float(), float(), float(), float()]),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float()
+            ],
+            float()
+        )),
+        "fn(
+  #(Float, Float, Float, Float, Float, Float),
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+) -> Float"
+    );
+
+    assert_eq!(
+        pretty_print(fn_(
+            vec![tuple(vec![
+                float(),
+                float(),
+                float(),
+                float(),
+                float(),
+                float()
+            ]),],
+            tuple(vec![
+                tuple(vec![float(), float(), float(), float(), float(), float()]),
+                tuple(vec![float(), float(), float(), float(), float(), float()]),
+            ]),
+        )),
+        "fn(#(Float, Float, Float, Float, Float, Float)) ->
+  #(
+    #(Float, Float, Float, Float, Float, Float),
+    #(Float, Float, Float, Float, Float, Float),
+  )"
+    );
+}
+
+#[test]
+fn gutter_test() {
+    let type_ = fn_(
+        vec![
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+            float(),
+        ],
+        float(),
+    );
+    assert_eq!(
+        Printer::new().pretty_print_gutter(&type_, 0, "// "),
+        "// fn(
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+//   Float,
+// ) -> Float"
+    );
+}
+
+#[test]
+fn gutter_uses_printer_settings_test() {
+    let one = named("", "one", "Wibble", Publicity::Public, vec![]);
+    let two = named("", "two", "Wibble", Publicity::Public, vec![]);
+    let mut printer = Printer::new();
+    printer.with_post_processor(Box::new(|rendered| rendered.to_uppercase()));
+    assert_eq!(
+        printer.pretty_print_gutter(&fn_(vec![two], one), 2, "// "),
+        "//   FN(WIBBLE) -> ONE.WIBBLE"
+    );
+}
+
+#[cfg(test)]
+fn pretty_print(typ: Arc<Type>) -> String {
+    Printer::new().pretty_print(&typ, 0)
+}
+
+#[test]
+fn default_value_int_test() {
+    assert_eq!(
+        Printer::new().print_default_value(&int()),
+        Some("0".to_string())
+    );
+}
+
+#[test]
+fn default_value_list_test() {
+    assert_eq!(
+        Printer::new().print_default_value(&list(generic_var(0))),
+        Some("[]".to_string())
+    );
+}
+
+#[test]
+fn default_value_function_test() {
+    assert_eq!(
+        Printer::new().print_default_value(&fn_(vec![int()], bool())),
+        None
+    );
+}
+
+#[test]
+fn qualifier_overrides_test() {
+    let mut printer = Printer::new();
+    printer.with_qualifier_overrides(im::hashmap! {"gleam/option".into() => "O".into()});
+    let type_ = tuple(vec![
+        named("", "wibble", "Option", Publicity::Public, vec![]),
+        named("", "gleam/option", "Option", Publicity::Public, vec![int()]),
+    ]);
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "#(wibble.Option, O.Option(Int))"
+    );
+}
+
+#[test]
+fn semantic_tokens_test() {
+    let tokens = Printer::new().semantic_tokens(&fn_(vec![generic_var(0)], int()));
+    let token = |delta_start, length, token_type| SemanticToken {
+        delta_line: 0,
+        delta_start,
+        length,
+        token_type,
+        token_modifiers_bitset: 0,
+    };
+    assert_eq!(tokens, vec![token(0, 2, 0), token(3, 1, 1), token(6, 3, 2)]);
+}
+
+#[test]
+fn semantic_tokens_uppercase_vars_test() {
+    let type_ = fn_(vec![generic_var(0)], generic_var(1));
+    let mut printer = Printer::new();
+    printer.with_var_naming(VarNaming::Uppercase);
+    printer.with_defaulted_marks(im::hashmap! {1 => int()});
+    // fn(A) -> Int /* defaulted */
+    let tokens = printer.semantic_tokens(&type_);
+    let token = |delta_start, length, token_type| SemanticToken {
+        delta_line: 0,
+        delta_start,
+        length,
+        token_type,
+        token_modifiers_bitset: 0,
+    };
+    assert_eq!(
+        tokens,
+        vec![
+            token(0, 2, 0),
+            token(3, 1, 1),
+            token(6, 3, 2),
+            token(4, 15, 5)
+        ]
+    );
+}
+
+#[test]
+fn copy_ready_test() {
+    let type_ = fn_(
+        vec![named(
+            "gleam_stdlib",
+            "gleam/option",
+            "Option",
+            Publicity::Public,
+            vec![int()],
+        )],
+        named("", "wibble", "Wobble", Publicity::Public, vec![]),
+    );
+    assert_eq!(
+        Printer::new().copy_ready(&type_, "wibble"),
+        CopyPayload {
+            annotation: "fn(option.Option(Int)) -> Wobble".into(),
+            imports: vec!["import gleam/option".into()],
+        }
+    );
+}
+
+#[test]
+fn copy_ready_type_names_test() {
+    let type_ = list(named(
+        "gleam_stdlib",
+        "gleam/option",
+        "Option",
+        Publicity::Public,
+        vec![int()],
+    ));
+    let mut names = TypeNames::new("wibble".into());
+    names.imported_module("gleam/option".into(), "opt".into());
+    let mut printer = Printer::new();
+    printer.with_type_names(names);
+    assert_eq!(
+        printer.copy_ready(&type_, "wibble"),
+        CopyPayload {
+            annotation: "List(opt.Option(Int))".into(),
+            imports: vec![],
+        }
+    );
+
+    // Names in scope in another module don't apply.
+    assert_eq!(
+        printer.copy_ready(&type_, "wobble"),
+        CopyPayload {
+            annotation: "List(option.Option(Int))".into(),
+            imports: vec!["import gleam/option".into()],
+        }
+    );
+}
+
+#[test]
+fn copy_ready_var_legend_test() {
+    let type_ = fn_(vec![generic_var(0)], generic_var(0));
+    let mut printer = Printer::new();
+    printer.with_var_legend();
+    assert_eq!(
+        printer.copy_ready(&type_, "wibble"),
+        CopyPayload {
+            annotation: "fn(a) -> a".into(),
+            imports: vec![],
+        }
+    );
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, TypeNames::new("wibble".into())),
+        "fn(a) -> a"
+    );
+    assert_eq!(
+        printer.print_constructor(&[generic_var(0)], &list(generic_var(0))),
+        "fn(a) -> List(a)"
+    );
+}
+
+#[test]
+fn copy_ready_clashing_modules_test() {
+    let option = |module| named("", module, "Option", Publicity::Public, vec![]);
+    let type_ = tuple(vec![
+        option("gleam/option"),
+        list(option("one/option")),
+        option("gleam/option"),
+    ]);
+    assert_eq!(
+        Printer::new().copy_ready(&type_, "wibble"),
+        CopyPayload {
+            annotation: "#(option.Option, List(one_option.Option), option.Option)".into(),
+            imports: vec![
+                "import gleam/option".into(),
+                "import one/option as one_option".into(),
+            ],
+        }
+    );
+
+    // A module can't be imported under the name of a module that is
+    // imported already.
+    let mut names = TypeNames::new("wibble".into());
+    names.imported_module("other/option".into(), "option".into());
+    assert_eq!(
+        Printer::new().print_as_if_in_module(&type_, names),
+        "#(gleam_option.Option, List(one_option.Option), gleam_option.Option)"
+    );
+}
+
+#[test]
+fn discriminators_bool_test() {
+    assert_eq!(
+        Printer::new().print_discriminators(&bool()),
+        Some(vec!["True".to_string(), "False".to_string()])
+    );
+}
+
+#[test]
+fn discriminators_tuple_test() {
+    assert_eq!(
+        Printer::new().print_discriminators(&tuple(vec![int(), bool(), float()])),
+        Some(vec!["#(_, _, _)".to_string()])
+    );
+}
+
+#[test]
+fn post_processor_test() {
+    let mut printer = Printer::new();
+    printer.with_post_processor(Box::new(|rendered| rendered.to_uppercase()));
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![generic_var(0)], int()), 0),
+        "FN(A) -> INT"
+    );
+}
+
+#[test]
+fn min_inline_width_test() {
+    let mut printer = Printer::new();
+    assert_eq!(printer.min_inline_width(&fn_(vec![int()], int())), 14);
+
+    let floats = || tuple(vec![float(), float(), float(), float(), float(), float()]);
+    let type_ = fn_(vec![floats()], tuple(vec![floats(), floats()]));
+    assert_eq!(printer.min_inline_width(&type_), 142);
+}
+
+#[test]
+fn type_abbreviations_test() {
+    let mut printer = Printer::new();
+    printer.with_type_abbreviations(im::hashmap! {
+        ("app/http/request".into(), "IncomingRequestHeaders".into()) => "Hdrs".into(),
+        ("app/unused".into(), "Unused".into()) => "U".into(),
+    });
+    let headers = named(
+        "",
+        "app/http/request",
+        "IncomingRequestHeaders",
+        Publicity::Public,
+        vec![],
+    );
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![headers.clone(), int()], headers), 0),
+        "fn(Hdrs, Int) -> Hdrs"
+    );
+    assert_eq!(
+        printer.abbreviation_legend(),
+        vec!["Hdrs = app/http/request.IncomingRequestHeaders".to_string()]
+    );
+}
+
+#[test]
+fn fn_with_effects_test() {
+    let mut printer = Printer::new();
+    let type_ = fn_(vec![int()], bool());
+    assert_eq!(
+        printer.print_fn_with_effects(&type_, Some("io".into())),
+        "fn(Int) -> Bool !{io}"
+    );
+    assert_eq!(
+        printer.print_fn_with_effects(&type_, None),
+        "fn(Int) -> Bool"
+    );
+}
+
+#[test]
+fn fn_with_effects_qualifier_overrides_test() {
+    let mut printer = Printer::new();
+    printer.with_qualifier_overrides(im::hashmap! {"one".into() => "uno".into()});
+    let one = named("", "one", "MyType", Publicity::Public, vec![]);
+    let two = named("", "two", "MyType", Publicity::Public, vec![]);
+    assert_eq!(
+        printer.print_fn_with_effects(&fn_(vec![one], two), Some("io".into())),
+        "fn(uno.MyType) -> two.MyType !{io}"
+    );
+}
+
+#[test]
+fn holes_test() {
+    let type_ = fn_(vec![unbound_var(0)], unbound_var(1));
+    assert_eq!(
+        Printer::new().print_with_holes(&type_, &im::hashset! {1}),
+        "fn(a) -> _"
+    );
+}
+
+#[test]
+fn anchors_test() {
+    let type_ = named("", "wibble", "Wobble", Publicity::Public, vec![list(int())]);
+    let (rendered, anchors) = Printer::new().print_with_anchors(&type_);
+    assert_eq!(rendered, "Wobble(List(Int))");
+    assert_eq!(
+        anchors,
+        vec![
+            (0..17, "wibble.Wobble".into()),
+            (7..16, "gleam.List".into()),
+            (12..15, "gleam.Int".into()),
+        ]
+    );
+}
+
+#[test]
+fn anchors_repeated_wrapper_test() {
+    let type_ = tuple(vec![list(list(int())), string()]);
+    let mut printer = Printer::new();
+    printer.with_repeat_wrapper_superscript(2);
+    let (rendered, anchors) = printer.print_with_anchors(&type_);
+    assert_eq!(rendered, "#(List²(Int), String)");
+    assert_eq!(
+        anchors,
+        vec![
+            (2..13, "gleam.List".into()),
+            (9..12, "gleam.Int".into()),
+            (15..21, "gleam.String".into()),
+        ]
+    );
+}
+
+#[test]
+fn anchors_defaulted_test() {
+    let type_ = tuple(vec![generic_var(0), string()]);
+    let mut printer = Printer::new();
+    printer.with_defaulted_marks(im::hashmap! {0 => int()});
+    let (rendered, anchors) = printer.print_with_anchors(&type_);
+    assert_eq!(rendered, "#(Int /* defaulted */, String)");
+    assert_eq!(
+        anchors,
+        vec![(2..5, "gleam.Int".into()), (23..29, "gleam.String".into()),]
+    );
+}
+
+#[test]
+fn anchors_uppercase_vars_test() {
+    let type_ = tuple(vec![generic_var(0), int()]);
+    let mut printer = Printer::new();
+    printer.with_var_naming(VarNaming::Uppercase);
+    let (rendered, anchors) = printer.print_with_anchors(&type_);
+    assert_eq!(rendered, "#(A, Int)");
+    assert_eq!(anchors, vec![(5..8, "gleam.Int".into())]);
+}
+
+#[test]
+fn alpha_equivalent_test() {
+    assert!(alpha_equivalent(
+        &fn_(vec![generic_var(0)], generic_var(0)),
+        &fn_(vec![generic_var(1)], generic_var(1)),
+    ));
+}
+
+#[test]
+fn alpha_equivalent_recursive_link_test() {
+    let recursive_list = || {
+        let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+        let type_ = Arc::new(Type::Var { type_: var.clone() });
+        *var.borrow_mut() = TypeVar::Link {
+            type_: list(type_.clone()),
+        };
+        (var, type_)
+    };
+    let (one_var, one) = recursive_list();
+    let (other_var, other) = recursive_list();
+
+    assert!(alpha_equivalent(&one, &other));
+    assert!(!alpha_equivalent(&one, &list(int())));
+
+    // Break the cycles so the test doesn't leak them.
+    *one_var.borrow_mut() = TypeVar::Unbound { id: 0 };
+    *other_var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn not_alpha_equivalent_test() {
+    assert!(!alpha_equivalent(
+        &fn_(vec![generic_var(0)], generic_var(0)),
+        &fn_(vec![generic_var(0)], generic_var(1)),
+    ));
+}
+
+#[test]
+fn numeric_widths_test() {
+    let mut printer = Printer::new();
+    printer.with_numeric_widths(im::hashmap! {"Int".into() => 64});
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![int()], float()), 0),
+        "fn(Int(64)) -> Float"
+    );
+}
+
+#[test]
+fn index_key_recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: list(type_.clone()),
+    };
+
+    assert_eq!(Printer::print_index_key(&type_), "gleam.List(<recursive>)");
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn index_key_function_test() {
+    assert_eq!(
+        Printer::print_index_key(&fn_(vec![int(), bool()], bool())),
+        "fn(gleam.Int,gleam.Bool)->gleam.Bool"
+    );
+}
+
+#[test]
+fn index_key_nested_generic_test() {
+    let type_ = list(result(
+        generic_var(7),
+        tuple(vec![string(), generic_var(7)]),
+    ));
+    assert_eq!(
+        Printer::print_index_key(&type_),
+        "gleam.List(gleam.Result(a,#(gleam.String,a)))"
+    );
+}
+
+#[test]
+fn max_distinct_vars_test() {
+    let mut printer = Printer::new();
+    printer.with_max_distinct_vars(2);
+    let type_ = fn_(
+        vec![generic_var(0), generic_var(1), generic_var(2)],
+        generic_var(0),
+    );
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(a, b, _) -> a");
+}
+
+#[test]
+fn snippet_test() {
+    assert_eq!(
+        Printer::new().print_snippet(&fn_(vec![int(), bool()], crate::type_::nil())),
+        "${1:arg1}: Int, ${2:arg2}: Bool"
+    );
+}
+
+#[test]
+fn unbound_question_style_test() {
+    let mut printer = Printer::new();
+    printer.with_unbound_rendering(UnboundStyle::Question);
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![unbound_var(0)], unbound_var(0)), 0),
+        "fn(?) -> ?"
+    );
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![generic_var(1)], generic_var(1)), 0),
+        "fn(a) -> a"
+    );
+}
+
+#[test]
+fn unbound_letter_style_test() {
+    assert_eq!(
+        Printer::new().pretty_print(&fn_(vec![unbound_var(0)], unbound_var(0)), 0),
+        "fn(a) -> a"
+    );
+}
+
+#[test]
+fn refactor_preview_test() {
+    let old = fn_(vec![int(), string()], bool());
+    let new = fn_(vec![int(), string()], crate::type_::nil());
+    assert_eq!(
+        Printer::new().print_refactor_preview(&old, &new),
+        "fn(Int, String) -> *Bool* → fn(Int, String) -> *Nil*"
+    );
+}
+
+#[test]
+fn parameter_lines_test() {
+    let type_ = fn_(vec![int(), string()], bool());
+    assert_eq!(
+        Printer::new().print_parameter_lines(&[Some("count".into()), None], &type_),
+        vec![
+            "count: Int".to_string(),
+            "arg2: String".to_string(),
+            "-> Bool".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn path_to_offset_test() {
+    let mut printer = Printer::new();
+    let type_ = fn_(vec![int(), bool()], crate::type_::nil());
+    // fn(Int, Bool) -> Nil
+    assert_eq!(printer.path_to_offset(&type_, 9), Some(vec![1]));
+    assert_eq!(printer.path_to_offset(&type_, 18), Some(vec![2]));
+    assert_eq!(printer.path_to_offset(&type_, 0), Some(vec![]));
+    assert_eq!(printer.path_to_offset(&type_, 40), None);
+}
+
+#[test]
+fn path_to_offset_nested_test() {
+    let type_ = tuple(vec![int(), list(result(string(), float()))]);
+    // #(Int, List(Result(String, Float)))
+    assert_eq!(
+        Printer::new().path_to_offset(&type_, 28),
+        Some(vec![1, 0, 1])
+    );
+}
+
+#[test]
+fn path_to_offset_with_var_counts_test() {
+    let type_ = fn_(vec![generic_var(0), int()], generic_var(0));
+    let mut printer = Printer::new();
+    count_vars(&type_, &mut printer.var_counts);
+    // fn(a×2, Int) -> a×2
+    assert_eq!(printer.path_to_offset(&type_, 4), Some(vec![0]));
+    assert_eq!(printer.path_to_offset(&type_, 9), Some(vec![1]));
+    assert_eq!(printer.path_to_offset(&type_, 11), Some(vec![1]));
+    assert_eq!(printer.path_to_offset(&type_, 17), Some(vec![2]));
+}
+
+#[test]
+fn path_to_offset_repeated_wrapper_test() {
+    let type_ = list(list(list(int())));
+    let mut printer = Printer::new();
+    printer.with_repeat_wrapper_superscript(2);
+    // List³(Int)
+    assert_eq!(printer.path_to_offset(&type_, 8), Some(vec![0, 0, 0]));
+}
+
+#[test]
+fn quoted_unusual_names_test() {
+    let mut printer = Printer::new();
+    printer.with_quoted_unusual_names();
+    let type_ = tuple(vec![
+        named("", "wibble", "Wobble Thing", Publicity::Public, vec![]),
+        named("", "wobble", "Wobble Thing", Publicity::Public, vec![]),
+        named("", "wobble", "Wobble", Publicity::Public, vec![]),
+    ]);
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "#(`Wobble Thing`, wobble.`Wobble Thing`, Wobble)"
+    );
+}
+
+#[test]
+fn pretty_print_width_test() {
+    let type_ = fn_(vec![int(), string()], bool());
+    assert_eq!(
+        Printer::new().pretty_print_width(&type_, 0, 20),
+        "fn(Int, String) ->
+  Bool"
+    );
+    assert_eq!(
+        Printer::new().pretty_print_width(&type_, 0, 12),
+        "fn(
+  Int,
+  String,
+) -> Bool"
+    );
+}
+
+#[test]
+fn middle_ellipsis_test() {
+    let type_ = list(named(
+        "",
+        "wibble",
+        "SomeVeryExtremelyLongName",
+        Publicity::Public,
+        vec![],
+    ));
+    assert_eq!(
+        Printer::new().print_middle_ellipsis(&type_, 10),
+        "List(SomeV…Name)"
+    );
+}
+
+#[test]
+fn middle_ellipsis_multibyte_test() {
+    let type_ = named("", "wibble", "Ünïcödé_Ñämé", Publicity::Public, vec![]);
+    assert_eq!(Printer::new().print_middle_ellipsis(&type_, 5), "Ün…mé");
+}
+
+#[test]
+fn middle_ellipsis_zero_test() {
+    let type_ = named("", "wibble", "Wobble", Publicity::Public, vec![]);
+    assert_eq!(Printer::new().print_middle_ellipsis(&type_, 0), "");
+}
+
+#[test]
+fn ambiguous_names_test() {
+    let mut printer = Printer::new();
+    let _ = printer.pretty_print(&tuple(vec![int(), bool()]), 0);
+    let _ = printer.pretty_print(&named("", "wibble", "Bool", Publicity::Public, vec![]), 0);
+    let _ = printer.pretty_print(&named("", "wibble", "Wobble", Publicity::Public, vec![]), 0);
+    assert_eq!(printer.ambiguous_names(), vec![EcoString::from("Bool")]);
+}
+
+#[test]
+fn manifest_entry_test() {
+    let type_ = fn_(vec![int(), generic_var(0)], list(generic_var(0)));
+    assert_eq!(
+        Printer::new().print_manifest_entry("repeat", &[None, Some("item".into())], &type_),
+        json!({
+            "name": "repeat",
+            "parameters": [
+                {
+                    "label": null,
+                    "type": {"tag": "named", "module": "gleam", "name": "Int", "args": []},
+                },
+                {
+                    "label": "item",
+                    "type": {"tag": "var", "name": "a"},
+                },
+            ],
+            "return": {
+                "tag": "named",
+                "module": "gleam",
+                "name": "List",
+                "args": [{"tag": "var", "name": "a"}],
+            },
+        })
+    );
+}
+
+#[test]
+fn full_qualification_test() {
+    let wibble = named("thepackage", "one/two", "Wibble", Publicity::Public, vec![]);
+    let wobble = named("thepackage", "one/two", "Wobble", Publicity::Public, vec![]);
+    let type_ = fn_(
+        vec![int(), tuple(vec![wibble, list(string())])],
+        result(wobble, crate::type_::nil()),
+    );
+
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(Int, #(Wibble, List(String))) -> Result(Wobble, Nil)"
+    );
+
+    let mut printer = Printer::new();
+    printer.with_full_qualification();
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(gleam.Int, #(one/two.Wibble, gleam.List(gleam.String))) ->
+  gleam.Result(one/two.Wobble, gleam.Nil)"
+    );
+}
+
+#[test]
+fn var_naming_test() {
+    let type_ = fn_(
+        vec![generic_var(0), unbound_var(1)],
+        tuple(vec![generic_var(1), generic_var(0)]),
+    );
+
+    let mut printer = Printer::new();
+    printer.with_var_naming(VarNaming::Uppercase);
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(A, B) -> #(B, A)");
+
+    let mut printer = Printer::new();
+    printer.with_var_naming(VarNaming::Numbered { prefix: "t".into() });
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(t0, t1) -> #(t1, t0)");
+}
+
+#[test]
+fn print_with_optional_labels_test() {
+    let type_ = fn_(vec![int(), int(), string()], list(int()));
+    let labels = [Some("from".into()), Some("step".into()), None];
+
+    assert_eq!(
+        Printer::new().print_with_optional_labels(&labels, &[], &type_),
+        "fn(from: Int, step: Int, String) -> List(Int)"
+    );
+    assert_eq!(
+        Printer::new().print_with_optional_labels(&labels, &[false, true, true], &type_),
+        "fn(from: Int, step?: Int, String) -> List(Int)"
+    );
+}
+
+#[test]
+fn diff_json_test() {
+    let expected = fn_(vec![int(), string()], bool());
+    let got = fn_(vec![float(), string()], bool());
+    assert_eq!(
+        Printer::new().diff_json(&expected, &got),
+        json!({
+            "tag": "fn",
+            "args": [
+                {
+                    "tag": "mismatch",
+                    "expected": {"tag": "named", "module": "gleam", "name": "Int", "args": []},
+                    "got": {"tag": "named", "module": "gleam", "name": "Float", "args": []},
+                },
+                {"tag": "named", "module": "gleam", "name": "String", "args": []},
+            ],
+            "return": {"tag": "named", "module": "gleam", "name": "Bool", "args": []},
+        })
+    );
+}
+
+#[test]
+fn recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: list(type_.clone()),
+    };
+
+    assert_eq!(Printer::new().pretty_print(&type_, 0), "List(<recursive>)");
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn print_json_recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: list(type_.clone()),
+    };
+
+    assert_eq!(
+        Printer::new().print_json(&type_),
+        json!({
+            "tag": "named",
+            "module": "gleam",
+            "name": "List",
+            "args": [{"kind": "recursive", "display": "<recursive>"}],
+            "display": "List(<recursive>)",
+        })
+    );
+    assert_eq!(
+        Printer::new().diff_json(&type_, &list(int())),
+        json!({
+            "tag": "named",
+            "module": "gleam",
+            "name": "List",
+            "args": [{
+                "tag": "mismatch",
+                "expected": {"kind": "recursive"},
+                "got": {"tag": "named", "module": "gleam", "name": "Int", "args": []},
+            }],
+        })
+    );
+    assert_eq!(
+        Printer::new().print_refactor_preview(&type_, &list(int())),
+        "List(*<recursive>*) → List(*Int*)"
+    );
+    assert_eq!(
+        Printer::new().print_changes_from(&list(int()), &type_),
+        "List(<recursive>)"
+    );
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn print_default_value_recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: tuple(vec![int(), type_.clone()]),
+    };
+
+    assert_eq!(Printer::new().print_default_value(&type_), None);
+    assert_eq!(
+        Printer::new().print_discriminators(&type_),
+        Some(vec!["#(_, _)".to_string()])
+    );
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn recursive_link_with_qualifier_overrides_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    let wibble = named("", "one", "Wibble", Publicity::Public, vec![]);
+    *var.borrow_mut() = TypeVar::Link {
+        type_: tuple(vec![wibble, list(type_.clone())]),
+    };
+
+    assert_eq!(
+        named_type_occurrences(&type_)
+            .map(|(module, name)| format!("{module}.{name}"))
+            .collect_vec(),
+        vec!["one.Wibble", "gleam.List"]
+    );
+
+    let mut printer = Printer::new();
+    printer.with_qualifier_overrides(im::hashmap! {"one".into() => "uno".into()});
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "#(Wibble, List(<recursive>))"
+    );
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn alias_expansion_depth_test() {
+    let alias = |name: &str| named("thepackage", "wibble", name, Publicity::Public, vec![]);
+    let aliases = im::hashmap![
+        ("wibble".into(), "A".into()) => alias("B"),
+        ("wibble".into(), "B".into()) => alias("C"),
+        ("wibble".into(), "C".into()) => list(int()),
+    ];
+    let type_ = fn_(vec![alias("A")], alias("B"));
+
+    let mut printer = Printer::new();
+    printer.with_type_aliases(aliases.clone());
+    printer.with_alias_expansion_depth(1);
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(B) -> C");
+
+    let mut printer = Printer::new();
+    printer.with_type_aliases(aliases);
+    printer.with_alias_expansion_depth(3);
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(List(Int)) -> List(Int)"
+    );
+}
+
+#[test]
+fn cyclic_alias_expansion_test() {
+    let alias = |name: &str| named("thepackage", "wibble", name, Publicity::Public, vec![]);
+    let aliases = im::hashmap![
+        ("wibble".into(), "A".into()) => list(alias("B")),
+        ("wibble".into(), "B".into()) => alias("A"),
+    ];
+    let mut printer = Printer::new();
+    printer.with_type_aliases(aliases);
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![alias("A")], alias("B")), 0),
+        "fn(List(A)) -> List(B)"
+    );
+}
+
+#[test]
+fn print_screen_reader_test() {
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.print_screen_reader(&fn_(vec![int(), bool()], crate::type_::nil())),
+        "function taking Int and Bool returning Nil"
+    );
+    assert_eq!(
+        printer.print_screen_reader(&fn_(vec![], int())),
+        "function taking nothing returning Int"
+    );
+}
+
+#[test]
+fn print_screen_reader_nested_generic_test() {
+    let type_ = list(result(
+        tuple(vec![string(), generic_var(0), float()]),
+        generic_var(1),
+    ));
+    assert_eq!(
+        Printer::new().print_screen_reader(&type_),
+        "list of Result of tuple of String, a and Float and b"
+    );
+}
+
+#[test]
+fn print_screen_reader_recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: list(type_.clone()),
+    };
+
+    assert_eq!(
+        Printer::new().print_screen_reader(&type_),
+        "list of recursive"
+    );
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn print_json_test() {
+    let one = named("thepackage", "one", "Wibble", Publicity::Public, vec![]);
+    let two = named("thepackage", "two", "Wibble", Publicity::Public, vec![]);
+    let type_ = fn_(vec![one, generic_var(0)], tuple(vec![two, int()]));
+    assert_eq!(
+        Printer::new().print_json(&type_),
+        json!({
+            "tag": "fn",
+            "args": [
+                {"tag": "named", "module": "one", "name": "Wibble", "args": [], "display": "Wibble"},
+                {"tag": "var", "name": "a", "display": "a"},
+            ],
+            "return": {
+                "tag": "tuple",
+                "elems": [
+                    {"tag": "named", "module": "two", "name": "Wibble", "args": [], "display": "two.Wibble"},
+                    {"tag": "named", "module": "gleam", "name": "Int", "args": [], "display": "Int"},
+                ],
+                "display": "#(two.Wibble, Int)",
+            },
+            "display": "fn(Wibble, a) -> #(two.Wibble, Int)",
+        })
+    );
+}
+
+#[test]
+fn print_with_var_counts_test() {
+    let type_ = fn_(vec![generic_var(0), generic_var(0)], generic_var(1));
+    assert_eq!(
+        Printer::new().print_with_var_counts(&type_),
+        "fn(a×2, a×2) -> b×1"
+    );
+}
+
+#[test]
+fn print_with_var_counts_recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: list(type_.clone()),
+    };
+
+    let type_ = fn_(vec![type_, generic_var(1)], generic_var(1));
+    assert_eq!(
+        Printer::new().print_with_var_counts(&type_),
+        "fn(List(<recursive>), a×2) -> a×2"
+    );
+
+    // Break the cycle so the test doesn't leak it.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+}
+
+#[test]
+fn print_changes_from_test() {
+    let baseline = fn_(vec![int(), list(string())], bool());
+    let current = fn_(vec![int(), list(string())], result(bool(), string()));
+
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.print_changes_from(&baseline, &current),
+        "fn(…, …) -> Result(Bool, String)"
+    );
+    assert_eq!(printer.print_changes_from(&current, &current), "…");
+}
+
+#[test]
+fn print_changes_from_nested_test() {
+    let baseline = tuple(vec![int(), list(string()), float()]);
+    let current = tuple(vec![int(), list(int()), float()]);
+    assert_eq!(
+        Printer::new().print_changes_from(&baseline, &current),
+        "#(…, List(Int), …)"
+    );
+}
+
+#[test]
+fn qualifier_overrides_same_name_test() {
+    let mut printer = Printer::new();
+    printer.with_qualifier_overrides(im::hashmap! {"one".into() => "uno".into()});
+    let one = named("", "one", "MyType", Publicity::Public, vec![]);
+    let two = named("", "two", "MyType", Publicity::Public, vec![]);
+
+    assert_eq!(
+        printer.pretty_print(&tuple(vec![two.clone(), one.clone()]), 0),
+        "#(two.MyType, uno.MyType)"
+    );
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![one], two), 0),
+        "fn(uno.MyType) -> two.MyType"
+    );
+}
+
+#[test]
+fn print_with_labels_test() {
+    let type_ = fn_(vec![int(), bool(), string()], bool());
+
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer
+            .print_with_labels(&type_, &[Some("first".into()), None, Some("third".into())])
+            .to_pretty_string(80),
+        "fn(first: Int, Bool, third: String) -> Bool"
+    );
+    assert_eq!(
+        printer.print_with_labels(&type_, &[]).to_pretty_string(80),
+        "fn(Int, Bool, String) -> Bool"
+    );
+    assert_eq!(
+        printer
+            .print_with_labels(&int(), &[Some("first".into())])
+            .to_pretty_string(80),
+        "Int"
+    );
+}
+
+#[test]
+fn print_palette_entry_test() {
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.print_palette_entry(
+            "wibble",
+            &fn_(vec![int(), list(bool())], crate::type_::nil())
+        ),
+        "wibble(Int, List(Bool)) Nil"
+    );
+    assert_eq!(
+        printer.print_palette_entry("wobble", &fn_(vec![], fn_(vec![int()], int()))),
+        "wobble() fn(Int) -> Int"
+    );
+    assert_eq!(printer.print_palette_entry("answer", &int()), "answer Int");
+}
+
+#[test]
+fn defaulted_marks_test() {
+    let type_ = fn_(vec![unbound_var(0), unbound_var(1)], list(unbound_var(0)));
 
-    assert_string!(
-        Type::Named {
-            module: "whatever".into(),
-            package: "whatever".into(),
-            name: "Int".into(),
-            publicity: Publicity::Public,
-            args: vec![],
-        },
-        "Int",
+    let mut printer = Printer::new();
+    printer.with_defaulted_marks(im::hashmap! {0 => int()});
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(Int /* defaulted */, a) -> List(Int /* defaulted */)"
     );
-    assert_string!(
-        Type::Named {
-            module: "themodule".into(),
-            package: "whatever".into(),
-            name: "Pair".into(),
-            publicity: Publicity::Public,
-            args: vec![
-                Arc::new(Type::Named {
-                    module: "whatever".into(),
-                    package: "whatever".into(),
-                    name: "Int".into(),
-                    publicity: Publicity::Public,
-                    args: vec![],
-                }),
-                Arc::new(Type::Named {
-                    module: "whatever".into(),
-                    package: "whatever".into(),
-                    name: "Bool".into(),
-                    publicity: Publicity::Public,
-                    args: vec![],
-                }),
-            ],
-        },
-        "Pair(Int, Bool)",
+
+    assert_eq!(
+        Printer::new().pretty_print(&type_, 0),
+        "fn(a, b) -> List(a)"
     );
-    assert_string!(
-        Type::Fn {
-            args: vec![
-                Arc::new(Type::Named {
-                    args: vec![],
-                    module: "whatever".into(),
-                    package: "whatever".into(),
-                    name: "Int".into(),
-                    publicity: Publicity::Public,
-                }),
-                Arc::new(Type::Named {
-                    args: vec![],
-                    module: "whatever".into(),
-                    package: "whatever".into(),
-                    name: "Bool".into(),
-                    publicity: Publicity::Public,
-                }),
-            ],
-            retrn: Arc::new(Type::Named {
-                args: vec![],
-                module: "whatever".into(),
-                package: "whatever".into(),
-                name: "Bool".into(),
-                publicity: Publicity::Public,
-            }),
-        },
-        "fn(Int, Bool) -> Bool",
+}
+
+#[test]
+fn print_as_if_in_module_test() {
+    let wibble = named(
+        "thepackage",
+        "app/wibble",
+        "Wibble",
+        Publicity::Public,
+        vec![],
     );
-    assert_string!(
-        Type::Var {
-            type_: Arc::new(RefCell::new(TypeVar::Link {
-                type_: Arc::new(Type::Named {
-                    args: vec![],
-                    module: "whatever".into(),
-                    package: "whatever".into(),
-                    name: "Int".into(),
-                    publicity: Publicity::Public,
-                }),
-            })),
-        },
-        "Int",
+    let wobble = named(
+        "thepackage",
+        "app/wobble",
+        "Wobble",
+        Publicity::Public,
+        vec![],
+    );
+    let type_ = fn_(vec![wibble, list(wobble)], int());
+
+    let mut printer = Printer::new();
+    let mut names = TypeNames::new("app/wibble".into());
+    names.imported_module("app/wobble".into(), "wob".into());
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, names),
+        "fn(Wibble, List(wob.Wobble)) -> Int"
+    );
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, TypeNames::new("app/wobble".into())),
+        "fn(wibble.Wibble, List(Wobble)) -> Int"
+    );
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(Wibble, List(Wobble)) -> Int"
+    );
+}
+
+#[test]
+fn reset_var_counter_test() {
+    let one = named("", "one", "Wibble", Publicity::Public, vec![]);
+    let two = named("", "two", "Wibble", Publicity::Public, vec![]);
+
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![generic_var(0)], one), 0),
+        "fn(a) -> Wibble"
+    );
+    printer.reset_var_counter();
+    assert_eq!(
+        printer.pretty_print(&fn_(vec![generic_var(1), generic_var(2)], two), 0),
+        "fn(a, b) -> two.Wibble"
+    );
+}
+
+#[test]
+fn tuple_single_line_spans_test() {
+    let type_ = tuple(vec![int(), generic_var(0)]);
+    let mut printer = Printer::new();
+    printer.with_tuple_single_line_max_elems(2);
+    let (rendered, spans) = printer.print_spans(&type_);
+    assert_eq!(rendered, "#(Int, a)");
+    assert_eq!(
+        spans,
+        vec![
+            (0..2, TokenKind::Punctuation),
+            (2..5, TokenKind::TypeName),
+            (5..6, TokenKind::Punctuation),
+            (7..8, TokenKind::TypeVar),
+            (8..9, TokenKind::Punctuation),
+        ]
+    );
+}
+
+#[test]
+fn tuple_single_line_max_elems_test() {
+    let pair = tuple(vec![
+        result(list(string()), generic_var(0)),
+        result(list(float()), generic_var(1)),
+    ]);
+    let type_ = fn_(vec![pair.clone(), pair], bool());
+
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.pretty_print_width(&type_, 0, 40),
+        "fn(
+  #(
+    Result(List(String), a),
+    Result(List(Float), b),
+  ),
+  #(
+    Result(List(String), a),
+    Result(List(Float), b),
+  ),
+) -> Bool"
+    );
+
+    let mut printer = Printer::new();
+    printer.with_tuple_single_line_max_elems(2);
+    assert_eq!(
+        printer.pretty_print_width(&type_, 0, 40),
+        "fn(
+  #(Result(List(String), a), Result(List(Float), b)),
+  #(Result(List(String), a), Result(List(Float), b)),
+) -> Bool"
+    );
+}
+
+#[test]
+fn repeat_wrapper_superscript_test() {
+    let type_ = fn_(
+        vec![list(list(list(int())))],
+        list(list(result(list(list(string())), int()))),
+    );
+
+    let mut printer = Printer::new();
+    printer.with_repeat_wrapper_superscript(2);
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(List³(Int)) -> List²(Result(List²(String), Int))"
+    );
+
+    let mut printer = Printer::new();
+    printer.with_repeat_wrapper_superscript(3);
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(List³(Int)) -> List(List(Result(List(List(String)), Int)))"
+    );
+}
+
+#[test]
+fn repeat_wrapper_superscript_recursive_link_test() {
+    let var = Arc::new(RefCell::new(TypeVar::Unbound { id: 0 }));
+    let type_ = Arc::new(Type::Var { type_: var.clone() });
+    *var.borrow_mut() = TypeVar::Link {
+        type_: list(type_.clone()),
+    };
+
+    let mut printer = Printer::new();
+    printer.with_repeat_wrapper_superscript(2);
+    assert_eq!(
+        printer.pretty_print(&list(type_.clone()), 0),
+        "List²(List²(<recursive>))"
+    );
+
+    // A variable linked directly to itself.
+    let self_linked = Arc::new(RefCell::new(TypeVar::Unbound { id: 1 }));
+    *self_linked.borrow_mut() = TypeVar::Link {
+        type_: Arc::new(Type::Var {
+            type_: self_linked.clone(),
+        }),
+    };
+    let type_ = list(Arc::new(Type::Var {
+        type_: self_linked.clone(),
+    }));
+    assert_eq!(printer.pretty_print(&type_, 0), "List(<recursive>)");
+
+    // Break the cycles so the test doesn't leak them.
+    *var.borrow_mut() = TypeVar::Unbound { id: 0 };
+    *self_linked.borrow_mut() = TypeVar::Unbound { id: 1 };
+}
+
+#[test]
+fn pretty_print_truncated_tuple_test() {
+    let type_ = tuple(vec![
+        int(),
+        tuple(vec![int(), tuple(vec![int(), tuple(vec![int(), int()])])]),
+    ]);
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.pretty_print_truncated(&type_, 20),
+        "#(Int, #(…, …))"
+    );
+    assert_eq!(printer.pretty_print_truncated(&type_, 7), "#(…, …)");
+    assert_eq!(
+        printer.pretty_print_truncated(&type_, 100),
+        "#(Int, #(Int, #(Int, #(Int, Int))))"
+    );
+}
+
+#[test]
+fn pretty_print_truncated_fn_test() {
+    let type_ = fn_(vec![list(result(string(), int()))], bool());
+    assert_eq!(
+        Printer::new().pretty_print_truncated(&type_, 20),
+        "fn(List(…)) -> Bool"
+    );
+}
+
+#[test]
+fn pretty_print_truncated_multibyte_test() {
+    let type_ = named("", "wibble", "Ünïcödé", Publicity::Public, vec![]);
+    assert_eq!(Printer::new().pretty_print_truncated(&type_, 3), "Ün…");
+}
+
+#[test]
+fn pretty_print_truncated_zero_test() {
+    let type_ = list(int());
+    assert_eq!(Printer::new().pretty_print_truncated(&type_, 0), "");
+    assert_eq!(Printer::new().pretty_print_truncated(&type_, 1), "…");
+}
+
+#[test]
+fn print_structural_test() {
+    let placeholder = |kind, range| Placeholder { kind, range };
+    assert_eq!(
+        Printer::new().print_structural(&fn_(vec![generic_var(0)], int())),
+        (
+            "fn(a) -> Int".into(),
+            vec![
+                placeholder(PlaceholderKind::Slot, 3..4),
+                placeholder(PlaceholderKind::TypeVar, 3..4),
+                placeholder(PlaceholderKind::Slot, 9..12),
+                placeholder(PlaceholderKind::TypeName, 9..12),
+            ]
+        )
+    );
+}
+
+#[test]
+fn print_compat_summary_test() {
+    let entries = [
+        (
+            "wibble".into(),
+            (*fn_(vec![int()], int())).clone(),
+            (*fn_(vec![int()], int())).clone(),
+            Compat::Unchanged,
+        ),
+        (
+            "wobble".into(),
+            (*fn_(vec![int()], int())).clone(),
+            (*fn_(vec![string()], int())).clone(),
+            Compat::Broken,
+        ),
+    ];
+    assert_eq!(
+        Printer::new().print_compat_summary(&entries),
+        "✓ unchanged wibble: fn(Int) -> Int
+✗ broken wobble: fn(*Int*) -> Int → fn(*String*) -> Int"
+    );
+}
+
+#[test]
+fn module_qualifier_basename_test() {
+    let type_ = tuple(vec![
+        named("", "wibble", "Option", Publicity::Public, vec![]),
+        named(
+            "gleam_stdlib",
+            "gleam/option",
+            "Option",
+            Publicity::Public,
+            vec![int()],
+        ),
+    ]);
+    assert_eq!(
+        Printer::new().pretty_print(&type_, 0),
+        "#(Option, option.Option(Int))"
+    );
+}
+
+#[test]
+fn module_qualifier_basename_clash_test() {
+    let type_ = tuple(vec![
+        named("", "one/option", "Option", Publicity::Public, vec![]),
+        named(
+            "gleam_stdlib",
+            "gleam/option",
+            "Option",
+            Publicity::Public,
+            vec![int()],
+        ),
+    ]);
+    assert_eq!(
+        Printer::new().pretty_print(&type_, 0),
+        "#(Option, gleam/option.Option(Int))"
+    );
+}
+
+#[test]
+fn module_qualifier_later_basename_clash_test() {
+    let type_ = tuple(vec![
+        named("", "c", "Foo", Publicity::Public, vec![]),
+        named("", "a/x", "Foo", Publicity::Public, vec![]),
+        named("", "b/x", "Foo", Publicity::Public, vec![]),
+    ]);
+    assert_eq!(
+        Printer::new().pretty_print(&type_, 0),
+        "#(Foo, a/x.Foo, b/x.Foo)"
+    );
+}
+
+#[test]
+fn print_spans_test() {
+    let module_type = named("", "one/two", "Wibble", Publicity::Public, vec![]);
+    let type_ = fn_(
+        vec![module_type.clone(), module_type, generic_var(0)],
+        int(),
+    );
+    let mut printer = Printer::new();
+    printer.with_full_qualification();
+    let (rendered, spans) = printer.print_spans(&type_);
+    assert_eq!(
+        rendered,
+        "fn(one/two.Wibble, one/two.Wibble, a) -> gleam.Int"
+    );
+    let pieces = spans
+        .into_iter()
+        .map(|(range, kind)| (rendered.get(range).unwrap_or_default(), kind))
+        .collect_vec();
+    assert_eq!(
+        pieces,
+        vec![
+            ("fn", TokenKind::Keyword),
+            ("(", TokenKind::Punctuation),
+            ("one/two", TokenKind::Module),
+            (".", TokenKind::Punctuation),
+            ("Wibble", TokenKind::TypeName),
+            (",", TokenKind::Punctuation),
+            ("one/two", TokenKind::Module),
+            (".", TokenKind::Punctuation),
+            ("Wibble", TokenKind::TypeName),
+            (",", TokenKind::Punctuation),
+            ("a", TokenKind::TypeVar),
+            (")", TokenKind::Punctuation),
+            ("->", TokenKind::Punctuation),
+            ("gleam", TokenKind::Module),
+            (".", TokenKind::Punctuation),
+            ("Int", TokenKind::TypeName),
+        ]
+    );
+}
+
+#[test]
+fn print_spans_multiline_test() {
+    let long = named(
+        "",
+        "wibble",
+        "AVeryLongTypeNameIndeed",
+        Publicity::Public,
+        vec![],
+    );
+    let type_ = fn_(
+        vec![long.clone(), long.clone(), long.clone(), long],
+        generic_var(0),
+    );
+    let (rendered, spans) = Printer::new().print_spans(&type_);
+    assert_eq!(
+        rendered,
+        "fn(
+  AVeryLongTypeNameIndeed,
+  AVeryLongTypeNameIndeed,
+  AVeryLongTypeNameIndeed,
+  AVeryLongTypeNameIndeed,
+) -> a"
+    );
+    let type_names = spans
+        .into_iter()
+        .filter(|(_, kind)| *kind == TokenKind::TypeName)
+        .map(|(range, _)| range)
+        .collect_vec();
+    assert_eq!(type_names, vec![6..29, 33..56, 60..83, 87..110]);
+}
+
+#[test]
+fn print_spans_uppercase_vars_test() {
+    let type_ = fn_(vec![generic_var(0)], int());
+    let mut printer = Printer::new();
+    printer.with_var_naming(VarNaming::Uppercase);
+    let (rendered, spans) = printer.print_spans(&type_);
+    assert_eq!(rendered, "fn(A) -> Int");
+    let pieces = spans
+        .into_iter()
+        .map(|(range, kind)| (rendered.get(range).unwrap_or_default(), kind))
+        .collect_vec();
+    assert_eq!(
+        pieces,
+        vec![
+            ("fn", TokenKind::Keyword),
+            ("(", TokenKind::Punctuation),
+            ("A", TokenKind::TypeVar),
+            (")", TokenKind::Punctuation),
+            ("->", TokenKind::Punctuation),
+            ("Int", TokenKind::TypeName),
+        ]
+    );
+}
+
+#[test]
+fn print_spans_annotations_test() {
+    let type_ = tuple(vec![generic_var(0), generic_var(1), generic_var(1)]);
+    let mut printer = Printer::new();
+    printer.with_defaulted_marks(im::hashmap! {0 => int()});
+    let (rendered, spans) = printer.print_spans(&type_);
+    assert_eq!(rendered, "#(Int /* defaulted */, a, a)");
+    let pieces = spans
+        .into_iter()
+        .map(|(range, kind)| (rendered.get(range).unwrap_or_default(), kind))
+        .collect_vec();
+    assert_eq!(
+        pieces,
+        vec![
+            ("#(", TokenKind::Punctuation),
+            ("Int", TokenKind::TypeName),
+            ("/* defaulted */", TokenKind::Annotation),
+            (",", TokenKind::Punctuation),
+            ("a", TokenKind::TypeVar),
+            (",", TokenKind::Punctuation),
+            ("a", TokenKind::TypeVar),
+            (")", TokenKind::Punctuation),
+        ]
+    );
+
+    let type_ = fn_(vec![generic_var(0), int()], generic_var(0));
+    let mut printer = Printer::new();
+    count_vars(&type_, &mut printer.var_counts);
+    let (rendered, spans) = printer.print_spans(&type_);
+    assert_eq!(rendered, "fn(a×2, Int) -> a×2");
+    let annotations = spans
+        .into_iter()
+        .filter(|(_, kind)| *kind == TokenKind::Annotation)
+        .filter_map(|(range, _)| rendered.get(range))
+        .collect_vec();
+    assert_eq!(annotations, vec!["×2", "×2"]);
+}
+
+#[test]
+fn var_legend_test() {
+    let mut printer = Printer::new();
+    printer.with_var_legend();
+    assert_eq!(
+        printer.pretty_print(
+            &fn_(vec![generic_var(0), generic_var(1)], generic_var(0)),
+            0
+        ),
+        "fn(a, b) -> a
+-- where a, b are type parameters"
+    );
+    assert_eq!(
+        printer.pretty_print(&list(generic_var(1)), 0),
+        "List(b)
+-- where b is a type parameter"
+    );
+    assert_eq!(printer.pretty_print(&int(), 0), "Int");
+}
+
+#[test]
+fn print_record_migration_test() {
+    let old_fields = [
+        ("name".into(), string()),
+        ("age".into(), int()),
+        ("tags".into(), list(generic_var(0))),
+        ("nickname".into(), string()),
+    ];
+    let new_fields = [
+        ("name".into(), string()),
+        ("age".into(), float()),
+        ("tags".into(), list(generic_var(1))),
+        ("email".into(), result(string(), crate::type_::nil())),
+    ];
+    assert_eq!(
+        Printer::new().print_record_migration(&old_fields, &new_fields),
+        "~ age: Int → Float
+- nickname: String
++ email: Result(String, Nil)"
+    );
+}
+
+#[test]
+fn explicit_prelude_test() {
+    let wibble = named(
+        "thepackage",
+        "wibble",
+        "Wibble",
+        Publicity::Public,
+        vec![int()],
+    );
+    let type_ = fn_(
+        vec![
+            list(list(int())),
+            tuple(vec![wibble, result(bool(), fn_(vec![float()], string()))]),
+        ],
+        result(list(generic_var(0)), crate::type_::nil()),
+    );
+
+    let mut printer = Printer::new();
+    printer.with_explicit_prelude();
+    assert_eq!(
+        printer.pretty_print_width(&type_, 0, isize::MAX),
+        "fn(gleam.List(gleam.List(gleam.Int)), #(Wibble(gleam.Int), gleam.Result(gleam.Bool, fn(gleam.Float) -> gleam.String))) -> gleam.Result(gleam.List(a), gleam.Nil)"
+    );
+
+    // The prelude stays qualified when printing the type in another way too.
+    printer.with_repeat_wrapper_superscript(2);
+    assert_eq!(
+        printer.print_changes_from(&list(list(float())), &list(list(int()))),
+        "gleam.List(gleam.List(gleam.Int))"
     );
-    assert_string!(
-        Type::Var {
-            type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 2231 })),
-        },
-        "a",
+    assert_eq!(
+        printer.pretty_print(&list(list(int())), 0),
+        "gleam.List²(gleam.Int)"
     );
-    assert_string!(
-        fn_(
-            vec![Arc::new(Type::Var {
-                type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 78 })),
-            })],
-            Arc::new(Type::Var {
-                type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 2 })),
-            }),
-        ),
-        "fn(a) -> b",
+}
+
+#[test]
+fn named_type_occurrences_test() {
+    let wibble = named(
+        "thepackage",
+        "one/two",
+        "Wibble",
+        Publicity::Public,
+        vec![generic_var(0)],
     );
-    assert_string!(
-        fn_(
-            vec![Arc::new(Type::Var {
-                type_: Arc::new(RefCell::new(TypeVar::Generic { id: 78 })),
-            })],
-            Arc::new(Type::Var {
-                type_: Arc::new(RefCell::new(TypeVar::Generic { id: 2 })),
-            }),
-        ),
-        "fn(a) -> b",
+    let linked = Arc::new(Type::Var {
+        type_: Arc::new(RefCell::new(TypeVar::Link { type_: float() })),
+    });
+    let type_ = fn_(
+        vec![
+            list(result(wibble, string())),
+            tuple(vec![linked, generic_var(1)]),
+        ],
+        crate::type_::nil(),
+    );
+    let occurrences = named_type_occurrences(&type_)
+        .map(|(module, name)| format!("{module}.{name}"))
+        .collect_vec();
+    assert_eq!(
+        occurrences,
+        vec![
+            "gleam.List",
+            "gleam.Result",
+            "one/two.Wibble",
+            "gleam.String",
+            "gleam.Float",
+            "gleam.Nil",
+        ]
     );
 }
 
 #[test]
-fn function_test() {
-    assert_eq!(pretty_print(fn_(vec![], int())), "fn() -> Int");
+fn empty_tuple_test() {
+    // `#()` is the valid Gleam syntax for the empty tuple type, so it is
+    // printed as is.
+    let type_ = fn_(vec![tuple(vec![])], tuple(vec![]));
+    assert_eq!(Printer::new().pretty_print(&type_, 0), "fn(#()) -> #()");
+}
+
+#[test]
+fn bare_prelude_test() {
+    let custom_int = named("thepackage", "wibble", "Int", Publicity::Public, vec![]);
+
+    let mut printer = Printer::new();
+    assert_eq!(printer.pretty_print(&custom_int, 0), "Int");
+    assert_eq!(printer.pretty_print(&int(), 0), "gleam.Int");
+
+    let mut printer = Printer::new();
+    printer.with_bare_prelude();
+    assert_eq!(printer.pretty_print(&custom_int, 0), "Int");
+    assert_eq!(printer.pretty_print(&list(int()), 0), "List(Int)");
+}
+
+#[test]
+fn prelude_types_unqualified_test() {
+    for prelude_type in PreludeType::iter() {
+        let type_ = named(
+            PRELUDE_PACKAGE_NAME,
+            PRELUDE_MODULE_NAME,
+            prelude_type.name(),
+            Publicity::Public,
+            vec![],
+        );
+        let name = prelude_type.name();
+        assert_eq!(Printer::new().pretty_print(&type_, 0), name);
+        assert_eq!(Printer::new().copy_ready(&type_, "wibble").annotation, name);
+
+        let mut printer = Printer::new();
+        printer.with_bare_prelude();
+        let clashing = named("thepackage", "wibble", name, Publicity::Public, vec![]);
+        let _ = printer.pretty_print(&clashing, 0);
+        assert_eq!(printer.pretty_print(&type_, 0), name);
+    }
+}
+
+#[test]
+fn assign_name_test() {
+    let dict = |key, value| {
+        named(
+            "gleam_stdlib",
+            "gleam/dict",
+            "Dict",
+            Publicity::Public,
+            vec![key, value],
+        )
+    };
+    let type_ = fn_(
+        vec![
+            dict(generic_var(7), generic_var(9)),
+            generic_var(1),
+            generic_var(2),
+        ],
+        generic_var(9),
+    );
 
+    let mut printer = Printer::new();
+    printer.assign_name(7, "key".into());
+    printer.assign_name(9, "value".into());
     assert_eq!(
-        pretty_print(fn_(vec![int(), int(), int()], int())),
-        "fn(Int, Int, Int) -> Int"
+        printer.pretty_print(&type_, 0),
+        "fn(Dict(key, value), a, b) -> value"
     );
 
+    let mut printer = Printer::new();
+    printer.assign_name(7, "a".into());
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(Dict(a, b), c, d) -> b");
+}
+
+#[test]
+fn print_owned_test() {
+    let document = {
+        let type_ = fn_(
+            vec![named(
+                "gleam_stdlib",
+                "gleam/option",
+                "Option",
+                Publicity::Public,
+                vec![int()],
+            )],
+            generic_var(1),
+        );
+        Printer::new().print_owned(&type_)
+    };
+    assert_eq!(document.to_pretty_string(80), "fn(Option(Int)) -> a");
+}
+
+#[test]
+fn trailing_commas_test() {
+    let type_ = fn_((0..13).map(|_| float()).collect(), int());
+
+    let mut printer = Printer::new();
     assert_eq!(
-        pretty_print(fn_(
-            vec![
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float()
-            ],
-            float()
-        )),
+        printer.pretty_print(&type_, 0),
         "fn(
   Float,
   Float,
@@ -402,25 +4918,14 @@ fn function_test() {
   Float,
   Float,
   Float,
-) -> Float"
+) -> Int"
     );
 
+    let mut printer = Printer::new();
+    printer.with_trailing_commas(false);
     assert_eq!(
-        pretty_print(fn_(
-            vec![
-                tuple(vec![float(), float(), float(), float(), float(), float()]),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float()
-            ],
-            float()
-        )),
+        printer.pretty_print(&type_, 0),
         "fn(
-  #(Float, Float, Float, Float, Float, Float),
   Float,
   Float,
   Float,
@@ -428,33 +4933,359 @@ fn function_test() {
   Float,
   Float,
   Float,
-) -> Float"
+  Float,
+  Float,
+  Float,
+  Float,
+  Float,
+  Float
+) -> Int"
+    );
+}
+
+#[test]
+fn scope_names_test() {
+    let type_ = fn_(
+        vec![generic_var(3), generic_var(8), generic_var(9)],
+        tuple(vec![generic_var(3), generic_var(9)]),
     );
 
+    let mut names = TypeNames::new("wibble".into());
+    names.type_variable_in_scope(8, "a".into());
+    names.type_variable_in_scope(9, "value".into());
+    let mut printer = Printer::new();
+    printer.with_type_names(names);
     assert_eq!(
-        pretty_print(fn_(
-            vec![tuple(vec![
-                float(),
-                float(),
-                float(),
-                float(),
-                float(),
-                float()
-            ]),],
-            tuple(vec![
-                tuple(vec![float(), float(), float(), float(), float(), float()]),
-                tuple(vec![float(), float(), float(), float(), float(), float()]),
-            ]),
-        )),
-        "fn(#(Float, Float, Float, Float, Float, Float)) ->
-  #(
-    #(Float, Float, Float, Float, Float, Float),
-    #(Float, Float, Float, Float, Float, Float),
-  )"
+        printer.pretty_print(&type_, 0),
+        "fn(b, a, value) -> #(b, value)"
+    );
+
+    printer.reset_var_counter();
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(b, a, value) -> #(b, value)"
     );
 }
 
-#[cfg(test)]
-fn pretty_print(typ: Arc<Type>) -> String {
-    Printer::new().pretty_print(&typ, 0)
+#[test]
+fn refactor_preview_type_names_test() {
+    let old = list(named("", "a/option", "Option", Publicity::Public, vec![]));
+    let new = list(named("", "b/option", "Option", Publicity::Public, vec![]));
+    let mut printer = Printer::new();
+    printer.with_type_names(TypeNames::new("wibble".into()));
+    assert_eq!(
+        printer.print_refactor_preview(&old, &new),
+        "List(*option.Option*) → List(*b_option.Option*)"
+    );
+    assert_eq!(
+        printer.print_compat_summary(&[(
+            "wobble".into(),
+            (*old).clone(),
+            (*new).clone(),
+            Compat::Broken
+        )]),
+        "✗ broken wobble: List(*option.Option*) → List(*b_option.Option*)"
+    );
+    assert_eq!(
+        printer.print_changes_from(&old, &new),
+        "List(b_option.Option)"
+    );
+}
+
+#[test]
+fn printed_width_test() {
+    let option = |arg| {
+        named(
+            "gleam_stdlib",
+            "gleam/option",
+            "Option",
+            Publicity::Public,
+            vec![arg],
+        )
+    };
+    let types = [
+        int(),
+        fn_(vec![option(int()), generic_var(1)], tuple(vec![])),
+        fn_((0..13).map(|_| option(float())).collect(), int()),
+    ];
+
+    for type_ in types {
+        let expected = Printer::new()
+            .pretty_print(&type_, 0)
+            .lines()
+            .map(str::len)
+            .max();
+        assert_eq!(Some(Printer::new().printed_width(&type_)), expected);
+    }
+}
+
+#[test]
+fn alias_table_test() {
+    let dict = |key, value| {
+        named(
+            "gleam_stdlib",
+            "gleam/dict",
+            "Dict",
+            Publicity::Public,
+            vec![key, value],
+        )
+    };
+    let type_ = fn_(
+        vec![int(), dict(string(), int()), dict(int(), int())],
+        float(),
+    );
+    assert_eq!(
+        alias_table_key(&dict(string(), int())),
+        Some(("gleam/dict".into(), "Dict(gleam.String, gleam.Int)".into()))
+    );
+
+    let mut printer = Printer::new();
+    printer.with_alias_table(im::hashmap! {
+        ("gleam".into(), "Int".into()) => "UserId".into(),
+        ("gleam/dict".into(), "Dict(gleam.String, gleam.Int)".into()) => "Scores".into(),
+    });
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "fn(UserId, Scores, Dict(UserId, UserId)) -> Float"
+    );
+}
+
+#[test]
+fn print_with_field_labels_test() {
+    let labels: Vec<EcoString> = (1..=10).map(|i| eco_format!("field_{i}")).collect();
+    let type_ = named(
+        "app",
+        "app/record",
+        "Record",
+        Publicity::Public,
+        (0..10).map(|_| string()).collect(),
+    );
+
+    assert_eq!(
+        Printer::new()
+            .print_with_field_labels(&type_, Some(&labels))
+            .to_pretty_string(80),
+        "Record(
+  field_1: String,
+  field_2: String,
+  field_3: String,
+  field_4: String,
+  field_5: String,
+  field_6: String,
+  field_7: String,
+  field_8: String,
+  field_9: String,
+  field_10: String,
+)"
+    );
+    assert_eq!(
+        Printer::new()
+            .print_with_field_labels(&type_, None)
+            .to_pretty_string(80),
+        Printer::new().print(&type_).to_pretty_string(80)
+    );
+}
+
+#[test]
+fn next_letter_skips_keywords_test() {
+    let mut printer = Printer::new();
+    let names = (0..26 * 27 * 2)
+        .map(|_| printer.next_letter())
+        .collect_vec();
+    for keyword in ["as", "fn", "if", "let", "pub", "use"] {
+        assert!(!names.iter().any(|name| name == keyword));
+    }
+    assert!(names.iter().any(|name| name == "at"));
+}
+
+#[test]
+fn print_head_test() {
+    let result = result(int(), string());
+    let linked = Type::Var {
+        type_: Arc::new(RefCell::new(TypeVar::Link { type_: string() })),
+    };
+    let mut printer = Printer::new();
+    assert_eq!(printer.print_head(&result), "Result/2");
+    assert_eq!(printer.print_head(&int()), "Int/0");
+    assert_eq!(
+        printer.print_head(&fn_(vec![int(), result.clone()], crate::type_::nil())),
+        "fn/2"
+    );
+    assert_eq!(
+        printer.print_head(&tuple(vec![int(), float(), result])),
+        "#/3"
+    );
+    assert_eq!(printer.print_head(&generic_var(0)), "a");
+    assert_eq!(printer.print_head(&linked), "String/0");
+
+    let self_linked = Arc::new(RefCell::new(TypeVar::Unbound { id: 1 }));
+    let type_ = Arc::new(Type::Var {
+        type_: self_linked.clone(),
+    });
+    *self_linked.borrow_mut() = TypeVar::Link {
+        type_: type_.clone(),
+    };
+    assert_eq!(printer.print_head(&type_), "<recursive>");
+    // Break the cycle so the test doesn't leak it.
+    *self_linked.borrow_mut() = TypeVar::Unbound { id: 1 };
+}
+
+#[test]
+fn print_with_phantom_params_test() {
+    let type_ = named(
+        "app",
+        "app/tagged",
+        "Tagged",
+        Publicity::Public,
+        vec![generic_var(0), int()],
+    );
+
+    assert_eq!(
+        Printer::new()
+            .print_with_phantom_params(&type_, &[true, false])
+            .to_pretty_string(80),
+        "Tagged(phantom a, Int)"
+    );
+    assert_eq!(
+        Printer::new()
+            .print_with_phantom_params(&type_, &[])
+            .to_pretty_string(80),
+        "Tagged(a, Int)"
+    );
+}
+
+#[test]
+fn print_constructor_test() {
+    let my_type = named("app", "app/thing", "MyType", Publicity::Public, vec![]);
+    assert_eq!(
+        Printer::new().print_constructor(&[int(), string()], &my_type),
+        "fn(Int, String) -> MyType"
+    );
+}
+
+#[test]
+fn package_qualification_test() {
+    let thing = |package| named(package, "wibble/one", "Thing", Publicity::Public, vec![]);
+    let type_ = tuple(vec![thing("first"), thing("second"), thing("first")]);
+
+    let mut printer = Printer::new();
+    printer.with_package_qualification();
+    assert_eq!(
+        printer.pretty_print(&type_, 0),
+        "#(Thing, second:wibble/one.Thing, Thing)"
+    );
+
+    let type_ = tuple(vec![thing("first"), int()]);
+    let mut printer = Printer::new();
+    printer.with_package_qualification();
+    assert_eq!(printer.pretty_print(&type_, 0), "#(Thing, Int)");
+
+    let other = named("second", "wibble/one", "Other", Publicity::Public, vec![]);
+    let type_ = tuple(vec![thing("first"), other]);
+    let mut printer = Printer::new();
+    printer.with_package_qualification();
+    assert_eq!(printer.pretty_print(&type_, 0), "#(Thing, Other)");
+}
+
+#[test]
+fn raw_var_ids_test() {
+    let type_ = fn_(
+        vec![Arc::new(Type::Var {
+            type_: Arc::new(RefCell::new(TypeVar::Unbound { id: 2231 })),
+        })],
+        generic_var(7),
+    );
+
+    let mut printer = Printer::new();
+    printer.with_raw_var_ids();
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(#2231) -> #7");
+
+    assert_eq!(Printer::new().pretty_print(&type_, 0), "fn(a) -> b");
+}
+
+#[test]
+fn pretty_print_single_line_test() {
+    let type_ = fn_((0..13).map(|_| float()).collect(), int());
+    assert_eq!(
+        Printer::new().pretty_print_single_line(&type_),
+        "fn(Float, Float, Float, Float, Float, Float, Float, Float, Float, Float, Float, \
+Float, Float) -> Int"
+    );
+}
+
+#[test]
+fn shared_var_names_across_arguments_test() {
+    let type_ = fn_(
+        vec![tuple(vec![generic_var(4)]), tuple(vec![generic_var(4)])],
+        generic_var(4),
+    );
+    assert_eq!(
+        Printer::new().pretty_print(&type_, 0),
+        "fn(#(a), #(a)) -> a"
+    );
+
+    let mut printer = Printer::new();
+    printer.with_tuple_single_line_max_elems(1);
+    assert_eq!(printer.pretty_print(&type_, 0), "fn(#(a), #(a)) -> a");
+}
+
+#[test]
+fn strict_test() {
+    let my_type = |module| named("app", module, "MyType", Publicity::Public, vec![]);
+    let type_ = tuple(vec![my_type("app/main"), my_type("app/other")]);
+    // import app/other.{type MyType}
+    let mut names = TypeNames::new("app/main".into());
+    names.named_type_in_scope("app/other".into(), "MyType".into(), "MyType".into());
+
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, names.clone()),
+        "#(MyType, MyType)"
+    );
+
+    let mut printer = Printer::new();
+    printer.with_strict();
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, names.clone()),
+        "#(MyType, other.MyType)"
+    );
+
+    let mut printer = Printer::new();
+    printer.with_strict();
+    assert_eq!(
+        printer.print_as_if_in_module(&my_type("app/other"), names),
+        "MyType"
+    );
+}
+
+#[test]
+fn strict_aliased_import_test() {
+    let wibble = named("app", "app/other", "Wibble", Publicity::Public, vec![]);
+    let my_type = named("app", "app/main", "MyType", Publicity::Public, vec![]);
+    let type_ = tuple(vec![my_type, wibble]);
+
+    // import app/other.{type Wibble as Wobble}
+    let mut names = TypeNames::new("app/main".into());
+    names.named_type_in_scope("app/other".into(), "Wibble".into(), "Wobble".into());
+    let mut printer = Printer::new();
+    printer.with_strict();
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, names),
+        "#(MyType, Wobble)"
+    );
+
+    // import app/other.{type Wibble as MyType}
+    let mut names = TypeNames::new("app/main".into());
+    names.named_type_in_scope("app/other".into(), "Wibble".into(), "MyType".into());
+    let mut printer = Printer::new();
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, names.clone()),
+        "#(MyType, MyType)"
+    );
+    let mut printer = Printer::new();
+    printer.with_strict();
+    assert_eq!(
+        printer.print_as_if_in_module(&type_, names),
+        "#(MyType, other.Wibble)"
+    );
 }
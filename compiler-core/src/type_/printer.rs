@@ -9,7 +9,7 @@ use crate::type_::{Type, TypeVar};
 /// This class keeps track of what names are used for modules in the current
 /// scope, so they can be printed in errors, etc.
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TypeNames {
     uid: u64,
     current_module: EcoString,
@@ -130,6 +130,31 @@ impl TypeNames {
         _ = self.imported_modules.insert(module_name, module_alias)
     }
 
+    /// The name of the module these names are in scope in.
+    pub fn current_module(&self) -> &EcoString {
+        &self.current_module
+    }
+
+    /// The name a module is referred to by in this module, if it is imported.
+    pub fn imported_module_alias(&self, module_name: &str) -> Option<&EcoString> {
+        self.imported_modules.get(module_name)
+    }
+
+    /// Whether an imported module is referred to by this name in this module.
+    pub fn module_alias_is_taken(&self, alias: &str) -> bool {
+        self.imported_modules.values().any(|taken| taken == alias)
+    }
+
+    /// The name a type variable has been given in this module, if any.
+    pub fn type_variable_in_scope_name(&self, id: u64) -> Option<&EcoString> {
+        self.type_variables.get(&id)
+    }
+
+    /// Whether a type variable in this module has been given this name.
+    pub fn type_variable_name_is_taken(&self, name: &str) -> bool {
+        self.type_variable_names.contains(name)
+    }
+
     /// Get the name and optional module qualifier for a named type.
     pub fn named_type<'a>(
         &'a self,